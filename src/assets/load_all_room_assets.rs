@@ -1,122 +1,269 @@
-use bevy::prelude::*;
+use bevy::{
+    asset::{LoadState, RecursiveDependencyLoadState},
+    prelude::*,
+    render::texture::{ImageLoaderSettings, ImageSampler},
+};
 use bevy_asset_loader::prelude::*;
 use serde::Deserialize;
 
+/// Where the room manifest lives, relative to the assets directory
+pub(crate) const ROOM_MANIFEST_PATH: &str = "rooms/rooms.manifest.ron";
+
 #[derive(Debug, Default)]
 pub struct LoadAllRoomAssetsPlugin;
 
-// I know this is super cursed but bear with me
-
 impl Plugin for LoadAllRoomAssetsPlugin {
     fn build(&self, app: &mut App) {
-        #[cfg(not(target_arch = "wasm32"))]
-        let str = {
-            use std::io::Read;
-            let mut file = std::fs::File::open("assets/rooms/room_list.ron")
-                .inspect_err(|e| {
-                    panic!("Failed to open file 'assets/rooms/room_list.ron': {e}");
-                })
-                .expect("If it couldn't be opened we would have panicked already");
-            let mut str = String::new();
-            let _ = file.read_to_string(&mut str);
-            str
-        };
-
-        #[cfg(target_arch = "wasm32")]
-        let str = include_str!("../../assets/rooms/room_list.ron");
-
-        let rooms_to_load: RoomsToLoad = ron::from_str(&str)
-            .inspect_err(|e| {
-                panic!("Failed to parse file 'assets/rooms/room_list.ron': {e}");
-            })
-            .expect("If it couldn't be parsed we would have panicked already");
-
-        app.insert_resource(rooms_to_load.clone());
-        app.init_state::<State>();
-        for room_file in rooms_to_load.rooms.iter() {
-            app.add_loading_state(
-                LoadingState::new(State::Loading(room_file.to_string()))
-                    .continue_to_state(State::Waiting)
-                    .load_collection::<super::RoomInfoAsset>()
-                    .load_collection::<crate::room::RoomAssets>()
-                    .with_dynamic_assets_file::<StandardDynamicAssetCollection>(room_file),
+        app.init_resource::<RoomLoadErrors>()
+            .add_systems(
+                OnEnter(crate::states::AppState::RoomLoading),
+                start_loading_rooms,
+            )
+            .add_systems(
+                Update,
+                load_rooms.run_if(in_state(crate::states::AppState::RoomLoading)),
             );
-        }
-        app.add_systems(OnEnter(State::Waiting), queue_next_room);
-        app.add_systems(OnEnter(State::Failed), on_fail);
-        app.add_systems(OnEnter(State::AllFinished), on_finish);
-        app.add_systems(
-            OnEnter(crate::states::AppState::RoomLoading),
-            start_loading_rooms,
-        );
     }
 }
 
-#[derive(Debug, Default, States, Clone, Hash, Eq, PartialEq)]
-enum State {
-    #[default]
-    None,
-    Waiting,
-    Loading(String),
-    AllFinished,
-    Failed,
+/// Lists the rooms to load, and how they get connected to each other
+#[derive(Debug, Clone, Default, Asset, TypePath, Deserialize)]
+pub struct RoomManifest {
+    /// Paths to each room's `*.assets.ron` file
+    pub rooms: Vec<String>,
+    /// How the rooms get connected to each other
+    #[serde(default)]
+    pub graph: crate::room::GraphMode,
+    /// What happens when room and enemy files are edited while the game runs
+    #[serde(default)]
+    pub hot_reload: crate::room::HotReloadSettings,
 }
 
-#[derive(Debug, Clone, Default, Resource, Deserialize)]
-struct RoomsToLoad {
-    rooms: Vec<String>,
+/// Rooms that couldn't be loaded and were left out of the game, with the reason why
+#[derive(Debug, Default, Resource)]
+pub struct RoomLoadErrors {
+    pub errors: Vec<String>,
 }
 
-fn start_loading_rooms(mut next_state: ResMut<NextState<State>>) {
-    next_state.set(State::Waiting);
+/// Keeps track of the rooms while they load
+#[derive(Debug, Resource)]
+pub struct LoadingRooms {
+    manifest: Handle<RoomManifest>,
+    /// Empty until the manifest has loaded
+    rooms: Vec<LoadingRoom>,
+    manifest_loaded: bool,
 }
 
-fn on_finish(
-    mut next_app_state: ResMut<NextState<crate::states::AppState>>,
-    mut next_game_state: ResMut<NextState<crate::states::GameState>>,
-) {
-    next_app_state.set(crate::states::AppState::InMenu);
-    next_game_state.set(crate::states::GameState::MainMenu);
+impl LoadingRooms {
+    /// How many rooms are done loading, successfully or not, out of how many there are.
+    /// `None` until the manifest has loaded
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        if !self.manifest_loaded {
+            return None;
+        }
+        let done = self
+            .rooms
+            .iter()
+            .filter(|room| matches!(room.status, RoomStatus::Done | RoomStatus::Failed))
+            .count();
+        Some((done, self.rooms.len()))
+    }
+}
+
+#[derive(Debug)]
+struct LoadingRoom {
+    /// Path to the room's `*.assets.ron` file
+    path: String,
+    collection: Handle<StandardDynamicAssetCollection>,
+    status: RoomStatus,
 }
 
-fn on_fail(mut exit: EventWriter<AppExit>) {
-    exit.send(AppExit::Error(
-        std::num::NonZero::<u8>::new(1).expect("1 is nonzero"),
-    ));
+#[derive(Debug)]
+enum RoomStatus {
+    /// Waiting on the `*.assets.ron` file
+    Collection,
+    /// Waiting on the files the collection refers to, and everything they refer to
+    Assets(crate::room::RoomAssets),
+    Done,
+    Failed,
 }
 
-fn queue_next_room(
+fn start_loading_rooms(mut commands: Commands, asset_server: Res<AssetServer>) {
+    info!("start_loading_rooms: loading {ROOM_MANIFEST_PATH}");
+    commands.insert_resource(LoadingRooms {
+        manifest: asset_server.load(ROOM_MANIFEST_PATH),
+        rooms: Vec::new(),
+        manifest_loaded: false,
+    });
+}
+
+fn load_rooms(
     mut commands: Commands,
-    mut rooms: ResMut<crate::room::Rooms>,
-    mut rooms_to_load: ResMut<RoomsToLoad>,
-    room_assets: Option<Res<crate::room::RoomAssets>>,
-    room_info_asset: Option<Res<crate::assets::RoomInfoAsset>>,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingRooms>,
+    manifests: Res<Assets<RoomManifest>>,
+    collections: Res<Assets<StandardDynamicAssetCollection>>,
     room_infos: Res<Assets<crate::room::RoomInfo>>,
-    mut next_state: ResMut<NextState<State>>,
+    mut rooms: ResMut<crate::room::Rooms>,
+    mut errors: ResMut<RoomLoadErrors>,
+    mut next_app_state: ResMut<NextState<crate::states::AppState>>,
+    mut next_game_state: ResMut<NextState<crate::states::GameState>>,
 ) {
-    if let Some(room_assets) = room_assets {
-        if let Some(room_info_asset) = room_info_asset {
-            let room_info = room_infos.get(&room_info_asset.info).unwrap();
+    let loading = loading.as_mut();
+
+    if !loading.manifest_loaded {
+        match asset_server.load_state(&loading.manifest) {
+            LoadState::Loaded => {
+                let manifest = manifests
+                    .get(&loading.manifest)
+                    .expect("the manifest has loaded");
+                commands.insert_resource(manifest.graph);
+                commands.insert_resource(manifest.hot_reload);
+                // every room loads at the same time
+                loading.rooms = manifest
+                    .rooms
+                    .iter()
+                    .map(|path| LoadingRoom {
+                        path: path.clone(),
+                        collection: asset_server.load(path.clone()),
+                        status: RoomStatus::Collection,
+                    })
+                    .collect();
+                loading.manifest_loaded = true;
+                info!("load_rooms: loading {} rooms", loading.rooms.len());
+            }
+            LoadState::Failed(e) => {
+                let error = format!("{ROOM_MANIFEST_PATH}: {e}");
+                error!("load_rooms: {error}");
+                errors.errors.push(error);
+                loading.manifest_loaded = true;
+            }
+            _ => return,
+        }
+    }
 
-            let room_name = room_info.name.clone();
+    for room in loading.rooms.iter_mut() {
+        let result = match &room.status {
+            RoomStatus::Collection => match asset_server.load_state(&room.collection) {
+                LoadState::Loaded => {
+                    let collection = collections
+                        .get(&room.collection)
+                        .expect("the collection has loaded");
+                    room_assets(collection, &asset_server).map(RoomStatus::Assets)
+                }
+                LoadState::Failed(e) => Err(e.to_string()),
+                _ => continue,
+            },
+            RoomStatus::Assets(assets) => {
+                let handles = [
+                    assets.info.clone().untyped(),
+                    assets.background_texture.clone().untyped(),
+                    assets.obstacle_texture.clone().untyped(),
+                    assets.projectile.clone().untyped(),
+                ]
+                .into_iter()
+                .chain(assets.boss_stats.clone().map(Handle::untyped))
+                .chain(assets.boss_texture.clone().map(Handle::untyped));
+                let states: Vec<_> = handles
+                    .map(|handle| asset_server.recursive_dependency_load_state(&handle))
+                    .collect();
 
-            info!("{} successfully loaded.", room_name);
-            rooms
-                .map
-                .insert(room_name, (room_info.clone(), room_assets.clone()));
+                if states.contains(&RecursiveDependencyLoadState::Failed) {
+                    Err("couldn't load one of the files it refers to".to_string())
+                } else if states
+                    .iter()
+                    .all(|state| *state == RecursiveDependencyLoadState::Loaded)
+                {
+                    let info = room_infos
+                        .get(&assets.info)
+                        .expect("the room info has loaded");
+                    if rooms.map.contains_key(&info.name) {
+                        Err(format!("another room is already named {}", info.name))
+                    } else {
+                        info!("{} successfully loaded.", info.name);
+                        rooms
+                            .map
+                            .insert(info.name.clone(), (info.clone(), assets.clone()));
+                        Ok(RoomStatus::Done)
+                    }
+                } else {
+                    continue;
+                }
+            }
+            RoomStatus::Done | RoomStatus::Failed => continue,
+        };
 
-            // The loading system loop will replace these values, but this way it's cleaned up at the end
-            // The handles are being kept alive by the Rooms resource
-            commands.remove_resource::<crate::room::RoomAssets>();
-            commands.remove_resource::<crate::assets::RoomInfoAsset>();
-        }
+        room.status = match result {
+            Ok(status) => status,
+            Err(e) => {
+                let error = format!("{}: {e}", room.path);
+                error!("load_rooms: skipping room. {error}");
+                errors.errors.push(error);
+                RoomStatus::Failed
+            }
+        };
     }
 
-    if let Some(next) = rooms_to_load.rooms.pop() {
-        info!("Preparing to load {}...", next);
-        next_state.set(State::Loading(next));
-    } else {
-        info!("All rooms loaded!");
-        next_state.set(State::AllFinished);
+    let Some((done, total)) = loading.progress() else {
+        return;
+    };
+    if done < total {
+        return;
     }
+
+    if !rooms.map.contains_key(crate::room::START_ROOM) {
+        let error = format!(
+            "the starting room {} couldn't be loaded",
+            crate::room::START_ROOM
+        );
+        error!("load_rooms: {error}");
+        errors.errors.push(error);
+    }
+    info!("All rooms loaded! {} of {total} succeeded", rooms.map.len());
+    commands.remove_resource::<LoadingRooms>();
+    next_app_state.set(crate::states::AppState::InMenu);
+    next_game_state.set(crate::states::GameState::MainMenu);
+}
+
+/// Starts loading everything a room's `*.assets.ron` file refers to
+fn room_assets(
+    collection: &StandardDynamicAssetCollection,
+    asset_server: &AssetServer,
+) -> Result<crate::room::RoomAssets, String> {
+    let file = |key: &str| -> Result<Option<String>, String> {
+        match collection.0.get(key) {
+            None => Ok(None),
+            Some(StandardDynamicAsset::File { path }) => Ok(Some(path.clone())),
+            Some(_) => Err(format!("`{key}` should be a `File`")),
+        }
+    };
+    let image = |key: &str| -> Result<Option<Handle<Image>>, String> {
+        match collection.0.get(key) {
+            None => Ok(None),
+            Some(StandardDynamicAsset::File { path }) => Ok(Some(asset_server.load(path.clone()))),
+            Some(StandardDynamicAsset::Image { path, sampler }) => {
+                let sampler = sampler.clone();
+                Ok(Some(asset_server.load_with_settings(
+                    path.clone(),
+                    move |settings: &mut ImageLoaderSettings| {
+                        if let Some(sampler) = sampler.clone() {
+                            settings.sampler = ImageSampler::Descriptor(sampler.into());
+                        }
+                    },
+                )))
+            }
+            Some(_) => Err(format!("`{key}` should be an `Image`")),
+        }
+    };
+    let required = |key: &str| format!("missing required key `{key}`");
+
+    Ok(crate::room::RoomAssets {
+        info: asset_server.load(file("info")?.ok_or_else(|| required("info"))?),
+        background_texture: image("background_texture")?
+            .ok_or_else(|| required("background_texture"))?,
+        obstacle_texture: image("obstacle_texture")?.ok_or_else(|| required("obstacle_texture"))?,
+        boss_stats: file("boss_stats")?.map(|path| asset_server.load(path)),
+        boss_texture: image("boss_texture")?,
+        projectile: image("projectile")?.ok_or_else(|| required("projectile"))?,
+    })
 }
@@ -1,16 +1,19 @@
 use bevy::{
     asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
     prelude::*,
+    render::texture::{ImageLoaderSettings, ImageSampler},
 };
 
-use bevy_asset_loader::prelude::*;
-
 use bevy_common_assets::ron::RonAssetPlugin;
 
 use thiserror::Error;
 
 mod load_all_room_assets;
 
+pub(crate) use load_all_room_assets::{
+    LoadingRooms, RoomLoadErrors, RoomManifest, ROOM_MANIFEST_PATH,
+};
+
 #[derive(Debug, Default)]
 pub struct AssetsPlugin;
 
@@ -19,27 +22,24 @@ impl Plugin for AssetsPlugin {
         app.add_plugins((
             RonAssetPlugin::<crate::enemy::EnemyStats>::new(&["enemy.ron"]),
             RonAssetPlugin::<crate::enemy::BossStats>::new(&["boss.ron"]),
-            RonAssetPlugin::<crate::room::RoomInfo>::new(&["info.ron"]),
             RonAssetPlugin::<crate::menus::Story>::new(&["story.ron"]),
+            RonAssetPlugin::<crate::difficulty::DifficultyCurve>::new(&["difficulty.ron"]),
+            RonAssetPlugin::<RoomManifest>::new(&["manifest.ron"]),
             load_all_room_assets::LoadAllRoomAssetsPlugin,
         ))
+        .init_asset::<crate::room::RoomInfo>()
+        .register_asset_loader(RoomInfoLoader)
         .init_resource::<crate::room::Rooms>();
     }
 }
 
-#[derive(Debug, Clone, Resource, Reflect, AssetCollection)]
-pub struct RoomInfoAsset {
-    #[asset(key = "info")]
-    pub info: Handle<crate::room::RoomInfo>,
-}
-
 #[derive(Default)]
 struct EnemyStatsLoader;
 
-/// Possible errors that can be produced by [`EnemyStatsLoader`]
+/// Possible errors that can be produced by [`EnemyStatsLoader`] and [`RoomInfoLoader`]
 #[non_exhaustive]
 #[derive(Debug, Error)]
-enum EnemyStatsLoaderError {
+enum RonLoaderError {
     /// An [IO](std::io) Error
     #[error("Could not load asset: {0}")]
     Io(#[from] std::io::Error),
@@ -51,7 +51,7 @@ enum EnemyStatsLoaderError {
 impl AssetLoader for EnemyStatsLoader {
     type Asset = crate::enemy::EnemyStats;
     type Settings = ();
-    type Error = EnemyStatsLoaderError;
+    type Error = RonLoaderError;
     async fn load<'a>(
         &'a self,
         reader: &'a mut Reader<'_>,
@@ -68,3 +68,48 @@ impl AssetLoader for EnemyStatsLoader {
         &["enemy.ron"]
     }
 }
+
+/// Loads a room's `*.info.ron` file, along with the stats and sprites for its roster
+#[derive(Default)]
+struct RoomInfoLoader;
+
+impl AssetLoader for RoomInfoLoader {
+    type Asset = crate::room::RoomInfo;
+    type Settings = ();
+    type Error = RonLoaderError;
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut room_info = ron::de::from_bytes::<crate::room::RoomInfo>(&bytes)?;
+
+        for entry in room_info.roster.iter_mut() {
+            entry.handles.stats = load_context.load(&entry.stats);
+            entry.handles.texture = load_pixel_art(load_context, &entry.texture);
+            entry.handles.projectile = entry
+                .projectile
+                .as_ref()
+                .map(|path| load_pixel_art(load_context, path));
+        }
+
+        Ok(room_info)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["info.ron"]
+    }
+}
+
+/// Loads an image with nearest-neighbor sampling, like `sampler: Nearest` in an asset collection
+fn load_pixel_art(load_context: &mut LoadContext<'_>, path: &str) -> Handle<Image> {
+    load_context
+        .loader()
+        .with_settings(|settings: &mut ImageLoaderSettings| {
+            settings.sampler = ImageSampler::nearest();
+        })
+        .load(path.to_string())
+}
@@ -29,6 +29,8 @@ impl Plugin for AudioPlugin {
                     new_skill_sounds,
                     projectile_reflect_sounds,
                     heal_sounds,
+                    boss_phase_sounds,
+                    alert_sounds,
                     update_running_sound_emitter,
                 )
                     .run_if(in_state(crate::states::GameState::InGame)),
@@ -60,6 +62,10 @@ pub struct SoundAssets {
     pub running: Handle<AudioSource>,
     #[asset(key = "heal")]
     pub heal: Handle<AudioSource>,
+    #[asset(key = "boss_phase")]
+    pub boss_phase: Handle<AudioSource>,
+    #[asset(key = "alert")]
+    pub alert: Handle<AudioSource>,
 }
 
 #[derive(Debug, Resource)]
@@ -240,6 +246,63 @@ fn heal_sounds(
     }
 }
 
+fn boss_phase_sounds(
+    mut commands: Commands,
+    sound_assets: Res<SoundAssets>,
+    mut phase_changes: EventReader<crate::boss::BossPhaseChanged>,
+) {
+    for crate::boss::BossPhaseChanged { .. } in phase_changes.read() {
+        commands.spawn(AudioSourceBundle {
+            settings: PlaybackSettings {
+                mode: bevy::audio::PlaybackMode::Despawn,
+                ..Default::default()
+            },
+            source: sound_assets.boss_phase.clone(),
+        });
+    }
+}
+
+/// Enemies that spot the player call out, and allies they warn answer more quietly.
+/// Only one ally answers each frame, so a boss warning the whole room isn't deafening.
+/// Enemies that only heard something stay silent
+fn alert_sounds(
+    mut commands: Commands,
+    sound_assets: Res<SoundAssets>,
+    mut alerts: EventReader<crate::enemy::EnemyAlertEvent>,
+    enemy_query: Query<&GlobalTransform>,
+) {
+    let mut answered = false;
+    for crate::enemy::EnemyAlertEvent { enemy, cause } in alerts.read() {
+        let volume = match cause {
+            crate::enemy::AlertCause::Sight => 1.0,
+            crate::enemy::AlertCause::Shout if answered => continue,
+            crate::enemy::AlertCause::Shout => {
+                answered = true;
+                0.4
+            }
+            crate::enemy::AlertCause::Noise => continue,
+        };
+        let Ok(transform) = enemy_query.get(*enemy) else {
+            continue;
+        };
+        commands.spawn((
+            SpatialBundle {
+                transform: Transform::from_translation(transform.translation()),
+                ..Default::default()
+            },
+            AudioSourceBundle {
+                settings: PlaybackSettings {
+                    mode: bevy::audio::PlaybackMode::Despawn,
+                    spatial: true,
+                    volume: bevy::audio::Volume::new(volume),
+                    ..Default::default()
+                },
+                source: sound_assets.alert.clone(),
+            },
+        ));
+    }
+}
+
 #[derive(Debug, Default, Component)]
 struct RunningSoundEmitter;
 
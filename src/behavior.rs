@@ -0,0 +1,427 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// What an enemy does, as a small state machine. Enemies start in the first state
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct Behavior {
+    pub states: Vec<BehaviorState>,
+}
+
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct BehaviorState {
+    /// Used by transitions to refer to this state
+    pub name: String,
+    pub action: Action,
+    /// Whether ranged enemies fire projectiles at the player in this state
+    #[serde(default)]
+    pub shoots: bool,
+    /// Checked in order every frame. The first one whose conditions all hold is taken
+    #[serde(default)]
+    pub transitions: Vec<Transition>,
+}
+
+/// How an enemy moves while it's in a state
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub enum Action {
+    /// Stands still
+    Idle,
+    /// Walks between `points` in order, given relative to where the enemy spawned
+    Patrol { points: Vec<Vec2> },
+    /// Ambles around at random
+    Wander,
+    /// Goes after the player, until it's `desired_distance` away from them
+    Chase,
+    /// Circles the player at `distance`, backing off if they get any closer
+    Kite { distance: f32 },
+    /// Runs away from the player
+    Flee,
+    /// Goes to where the player was last seen, and looks around
+    Search,
+    /// Goes back to where the enemy spawned
+    ReturnHome,
+}
+
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct Transition {
+    /// All of these have to hold. No conditions at all means the transition is always taken
+    #[serde(default)]
+    pub when: Vec<Condition>,
+    /// The name of the state to go to
+    pub to: String,
+    /// Shows that the enemy has noticed the player. If it saw them itself, it warns its allies
+    /// too, see `EnemyAlertEvent`
+    #[serde(default)]
+    pub alert: bool,
+}
+
+#[derive(Debug, Clone, Copy, Reflect, serde::Deserialize)]
+pub enum Condition {
+    /// The player is within `alert_radius` and the enemy's view cone, with nothing in the way
+    SeesPlayer,
+    /// The player is further away than `chase_radius`, or out of sight
+    LostPlayer,
+    /// The player is closer than this
+    PlayerWithin(f32),
+    /// The player is further away than this
+    PlayerBeyond(f32),
+    /// The enemy has less than this fraction of its health left
+    HealthBelow(f32),
+    /// The enemy has at least this fraction of its health left
+    HealthAbove(f32),
+    /// The enemy has been in this state for this many seconds
+    After(f32),
+    /// The enemy has made it to where it was going, for `Search` and `ReturnHome`
+    Arrived,
+    /// An ally that saw the player warned the enemy. It knows where they were seen
+    Alerted,
+    /// The enemy heard fighting within its `hearing_radius`. It knows where it came from
+    HeardNoise,
+}
+
+impl Behavior {
+    /// Wanders until it sees the player or is warned about them, chases them until they get
+    /// away, then searches for `search_time` seconds where they were last seen before going
+    /// back to wandering. Noises send it searching too
+    pub fn default_for(search_time: f32) -> Self {
+        let to_chase = Transition {
+            when: vec![Condition::SeesPlayer],
+            to: "chase".to_string(),
+            alert: true,
+        };
+        let warned = Transition {
+            when: vec![Condition::Alerted],
+            to: "chase".to_string(),
+            alert: true,
+        };
+        Behavior {
+            states: vec![
+                BehaviorState {
+                    name: "wander".to_string(),
+                    action: Action::Wander,
+                    shoots: false,
+                    transitions: vec![
+                        to_chase.clone(),
+                        warned.clone(),
+                        Transition {
+                            when: vec![Condition::HeardNoise],
+                            to: "search".to_string(),
+                            alert: true,
+                        },
+                    ],
+                },
+                BehaviorState {
+                    name: "chase".to_string(),
+                    action: Action::Chase,
+                    shoots: true,
+                    transitions: vec![Transition {
+                        when: vec![Condition::LostPlayer],
+                        to: "search".to_string(),
+                        alert: false,
+                    }],
+                },
+                BehaviorState {
+                    name: "search".to_string(),
+                    action: Action::Search,
+                    shoots: false,
+                    transitions: vec![
+                        to_chase,
+                        warned,
+                        Transition {
+                            when: vec![Condition::After(search_time)],
+                            to: "wander".to_string(),
+                            alert: false,
+                        },
+                    ],
+                },
+            ],
+        }
+    }
+
+    /// The index of the state called `name`
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|state| state.name == name)
+    }
+}
+
+impl crate::enemy::EnemyStats {
+    /// The behavior state the enemy is in
+    pub fn behavior_state(&self, enemy_state: &crate::enemy::EnemyState) -> Option<&BehaviorState> {
+        self.behavior
+            .as_ref()
+            .and_then(|behavior| behavior.states.get(enemy_state.current))
+    }
+}
+
+/// How close an enemy has to get to somewhere to count as being there
+pub const CLOSE_ENOUGH: f32 = 16.0;
+
+/// Is the player close enough and inside the enemy's view cone to be noticed, if nothing's
+/// in the way?
+fn in_view(
+    stats: &crate::enemy::EnemyStats,
+    facing: &crate::enemy::Facing,
+    enemy_pos: Vec2,
+    player_pos: Vec2,
+) -> bool {
+    let to_player = player_pos - enemy_pos;
+    if to_player.length() > stats.alert_radius {
+        return false;
+    }
+    stats
+        .view_cone
+        .is_none_or(|cone| facing.0.angle_between(to_player).abs().to_degrees() <= cone.angle * 0.5)
+}
+
+/// Is the view from `from` to `to` clear of obstacles and walls?
+fn line_of_sight(rapier_context: &RapierContext, from: Vec2, to: Vec2) -> bool {
+    let filter = QueryFilter::new().groups(CollisionGroups::new(
+        Group::ALL,
+        crate::physics::COLLISION_GROUP_OBSTACLE,
+    ));
+    rapier_context
+        .cast_ray(
+            from,
+            (to - from).normalize_or_zero(),
+            from.distance(to),
+            true,
+            filter,
+        )
+        .is_none()
+}
+
+/// Everything about an enemy and the player that transition conditions look at
+struct Senses<'a> {
+    stats: &'a crate::enemy::EnemyStats,
+    state: &'a crate::enemy::EnemyState,
+    health_fraction: f32,
+    enemy_pos: Vec2,
+    player_pos: Option<Vec2>,
+    sees_player: bool,
+    in_sight: bool,
+    heard: Option<crate::enemy::AlertCause>,
+}
+
+impl Senses<'_> {
+    fn holds(&self, condition: Condition) -> bool {
+        let player_distance = self
+            .player_pos
+            .map(|player_pos| player_pos.distance(self.enemy_pos));
+        match condition {
+            Condition::SeesPlayer => self.sees_player,
+            // once it's chasing, the enemy keeps turning to face the player, so only
+            // losing sight of them matters
+            Condition::LostPlayer => {
+                !self.in_sight
+                    || player_distance.is_none_or(|distance| distance > self.stats.chase_radius)
+            }
+            Condition::PlayerWithin(range) => player_distance.is_some_and(|d| d < range),
+            Condition::PlayerBeyond(range) => player_distance.is_none_or(|d| d > range),
+            Condition::HealthBelow(fraction) => self.health_fraction < fraction,
+            Condition::HealthAbove(fraction) => self.health_fraction >= fraction,
+            Condition::After(seconds) => self.state.time_in_state >= seconds,
+            Condition::Arrived => {
+                let destination = match self.stats.behavior_state(self.state).map(|s| &s.action) {
+                    Some(Action::Search) => self.state.last_seen,
+                    Some(Action::ReturnHome) => Some(self.state.home),
+                    _ => None,
+                };
+                destination.is_some_and(|pos| pos.distance(self.enemy_pos) < CLOSE_ENOUGH)
+            }
+            Condition::Alerted => self.heard == Some(crate::enemy::AlertCause::Shout),
+            Condition::HeardNoise => self.heard == Some(crate::enemy::AlertCause::Noise),
+        }
+    }
+}
+
+/// Moves enemies between the states of their `Behavior`
+pub fn update_behavior(
+    mut enemy_query: Query<(
+        Entity,
+        &mut crate::enemy::EnemyState,
+        &crate::enemy::EnemyStats,
+        &crate::enemy::EnemyHealth,
+        &crate::enemy::Facing,
+        &GlobalTransform,
+    )>,
+    player_query: Query<&GlobalTransform, With<crate::player::Player>>,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+    mut writer: EventWriter<crate::enemy::EnemyAlertEvent>,
+) {
+    let player_pos = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation().truncate());
+
+    for (enemy, mut enemy_state, stats, health, facing, transform) in enemy_query.iter_mut() {
+        let Some(behavior) = stats.behavior.as_ref() else {
+            continue;
+        };
+        // the behavior can change under it when its stats are reloaded
+        if enemy_state.current >= behavior.states.len() {
+            enemy_state.current = 0;
+            enemy_state.time_in_state = 0.0;
+        }
+        let enemy_pos = transform.translation().truncate();
+        enemy_state.time_in_state += time.delta_seconds();
+        // warnings and noises only count for the frame they're noticed in
+        let heard = enemy_state.heard.take();
+
+        // casting the ray is the expensive part, so it's only done when the player is in view
+        // or close enough to be chased. Further out, whether they're in sight doesn't matter
+        let facing_player =
+            player_pos.is_some_and(|player_pos| in_view(stats, facing, enemy_pos, player_pos));
+        let in_range = player_pos
+            .is_some_and(|player_pos| player_pos.distance(enemy_pos) <= stats.chase_radius);
+        let in_sight = (facing_player || in_range)
+            && player_pos
+                .is_some_and(|player_pos| line_of_sight(&rapier_context, enemy_pos, player_pos));
+        let sees_player = in_sight && facing_player;
+        if let Some(player_pos) = player_pos.filter(|_| in_sight) {
+            if sees_player || player_pos.distance(enemy_pos) <= stats.chase_radius {
+                enemy_state.last_seen = Some(player_pos);
+            }
+        }
+
+        // patrols carry on to the next point once they reach one
+        if let Some(Action::Patrol { points }) =
+            stats.behavior_state(&enemy_state).map(|s| &s.action)
+        {
+            let target = points
+                .get(enemy_state.patrol_index)
+                .map(|point| enemy_state.home + *point);
+            if target.is_some_and(|target| target.distance(enemy_pos) < CLOSE_ENOUGH) {
+                enemy_state.patrol_index = (enemy_state.patrol_index + 1) % points.len();
+            }
+        }
+
+        let senses = Senses {
+            stats,
+            state: &enemy_state,
+            health_fraction: health.current / health.maximum,
+            enemy_pos,
+            player_pos,
+            sees_player,
+            in_sight,
+            heard,
+        };
+        let Some(transition) = behavior.states.get(enemy_state.current).and_then(|state| {
+            state
+                .transitions
+                .iter()
+                .find(|transition| transition.when.iter().all(|c| senses.holds(*c)))
+        }) else {
+            continue;
+        };
+        let Some(next) = behavior.find(&transition.to) else {
+            warn!(
+                "update_behavior: no state called {:?} to go to",
+                transition.to
+            );
+            continue;
+        };
+
+        enemy_state.current = next;
+        enemy_state.time_in_state = 0.0;
+        enemy_state.patrol_index = 0;
+        if transition.alert {
+            let cause = match heard {
+                Some(cause) if !sees_player => cause,
+                _ => crate::enemy::AlertCause::Sight,
+            };
+            writer.send(crate::enemy::EnemyAlertEvent { enemy, cause });
+        }
+    }
+}
+
+/// Enemies that spot the player warn allies within their `shout_radius`, and bosses warn the
+/// whole room. Only enemies that saw the player themselves shout, so warnings don't spread
+/// any further than that
+pub fn propagate_alerts(
+    mut events: EventReader<crate::enemy::EnemyAlertEvent>,
+    mut enemy_query: Query<(
+        Entity,
+        &crate::enemy::EnemyStats,
+        &mut crate::enemy::EnemyState,
+        &GlobalTransform,
+        Has<crate::enemy::Boss>,
+    )>,
+) {
+    let mut shouts = vec![];
+    for crate::enemy::EnemyAlertEvent { enemy, cause } in events.read() {
+        if *cause != crate::enemy::AlertCause::Sight {
+            continue;
+        }
+        let Ok((_, stats, state, transform, is_boss)) = enemy_query.get(*enemy) else {
+            continue;
+        };
+        let Some(last_seen) = state.last_seen else {
+            continue;
+        };
+        let radius = if is_boss {
+            f32::INFINITY
+        } else {
+            stats.shout_radius
+        };
+        shouts.push((
+            *enemy,
+            transform.translation().truncate(),
+            radius,
+            last_seen,
+        ));
+    }
+    if shouts.is_empty() {
+        return;
+    }
+
+    for (ally, _, mut state, transform, _) in enemy_query.iter_mut() {
+        let ally_pos = transform.translation().truncate();
+        let Some((_, _, _, last_seen)) = shouts.iter().find(|(shouter, pos, radius, _)| {
+            *shouter != ally && pos.distance(ally_pos) <= *radius
+        }) else {
+            continue;
+        };
+        state.heard = Some(crate::enemy::AlertCause::Shout);
+        state.last_seen = Some(*last_seen);
+    }
+}
+
+/// Lets enemies with a `hearing_radius` hear melee fights and deaths nearby
+pub fn hear_noises(
+    mut melee_attacks: EventReader<crate::damage::MeleeAttackEvent>,
+    mut deaths: EventReader<crate::enemy::EnemyDeathEvent>,
+    player_query: Query<&GlobalTransform, With<crate::player::Player>>,
+    mut enemy_query: Query<(
+        &crate::enemy::EnemyStats,
+        &mut crate::enemy::EnemyState,
+        &GlobalTransform,
+    )>,
+) {
+    let mut noises: Vec<Vec2> = deaths.read().map(|death| death.pos).collect();
+    for crate::damage::MeleeAttackEvent { player, .. } in melee_attacks.read() {
+        if let Ok(transform) = player_query.get(*player) {
+            noises.push(transform.translation().truncate());
+        }
+    }
+    if noises.is_empty() {
+        return;
+    }
+
+    for (stats, mut state, transform) in enemy_query.iter_mut() {
+        let Some(hearing_radius) = stats.hearing_radius else {
+            continue;
+        };
+        // a warning from an ally says more than a noise does
+        if state.heard.is_some() {
+            continue;
+        }
+        let enemy_pos = transform.translation().truncate();
+        if let Some(noise) = noises
+            .iter()
+            .find(|noise| noise.distance(enemy_pos) <= hearing_radius)
+        {
+            state.heard = Some(crate::enemy::AlertCause::Noise);
+            state.last_seen = Some(*noise);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+
+/// How many times a second an invulnerable boss flashes
+const FLASH_RATE: f32 = 6.0;
+
+/// A stage of a boss fight. The boss moves on to it once its health drops below
+/// `health_below`, and keeps whatever the phase changes until a later phase changes it again
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct BossPhase {
+    /// Fraction of its maximum health the boss has to drop below to start this phase
+    pub health_below: f32,
+    /// Shown across the screen when the phase starts
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Replaces the boss's enemy type, and with it the way it attacks
+    #[serde(default)]
+    pub enemy_type: Option<crate::enemy::EnemyType>,
+    /// Replaces the boss's movement speed
+    #[serde(default)]
+    pub speed: Option<f32>,
+    /// Replaces the boss's behavior. The boss starts again from its first state
+    #[serde(default)]
+    pub behavior: Option<crate::behavior::Behavior>,
+    /// Enemies summoned around the boss when the phase starts
+    #[serde(default)]
+    pub summons: Vec<Summon>,
+    /// How many seconds the boss can't be hurt for once the phase starts
+    #[serde(default)]
+    pub invulnerable: f32,
+}
+
+/// Some enemies a boss calls in to help
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct Summon {
+    /// Path to the enemy's `*.enemy.ron` file. It has to be in the boss room's roster
+    pub enemy: String,
+    pub count: usize,
+}
+
+impl crate::enemy::BossStats {
+    /// The last phase the boss has reached with this fraction of its health left, if any
+    pub fn phase_at(&self, health_fraction: f32) -> Option<usize> {
+        self.phases
+            .iter()
+            .rposition(|phase| health_fraction < phase.health_below)
+    }
+
+    /// The boss's stats once it's reached `phase`, with everything the phases up to it
+    /// have changed
+    pub fn phase_stats(&self, phase: Option<usize>) -> crate::enemy::EnemyStats {
+        let mut stats = self.stats.clone();
+        let reached = phase.map_or(0, |phase| phase + 1);
+        for phase in self.phases.iter().take(reached) {
+            if let Some(enemy_type) = phase.enemy_type {
+                stats.enemy_type = enemy_type;
+            }
+            if let Some(speed) = phase.speed {
+                stats.speed = speed;
+            }
+            if let Some(behavior) = &phase.behavior {
+                stats.behavior = Some(behavior.clone());
+            }
+        }
+        stats
+            .behavior
+            .get_or_insert_with(|| crate::behavior::Behavior::default_for(stats.search_time));
+        stats
+    }
+}
+
+/// The phase a boss is in, `None` until it reaches its first one
+#[derive(Debug, Default, Component)]
+pub struct BossPhaseIndex(pub Option<usize>);
+
+/// Keeps a boss from being hurt for a while after it changes phase. It flashes until then
+#[derive(Debug, Component)]
+pub struct Invulnerable(pub Timer);
+
+/// A boss has moved on to a new phase of its fight
+#[derive(Debug, Clone, Event)]
+pub struct BossPhaseChanged {
+    pub boss: Entity,
+    /// Index of the phase in `BossStats::phases`
+    pub phase: usize,
+}
+
+/// Moves bosses on to the next phase of their fight once their health drops far enough.
+/// A hit that takes a boss past several thresholds at once skips straight to the last one
+pub fn update_boss_phases(
+    mut commands: Commands,
+    mut boss_query: Query<(
+        Entity,
+        &crate::enemy::BossStats,
+        &crate::enemy::EnemyHealth,
+        &mut BossPhaseIndex,
+        &mut crate::enemy::EnemyStats,
+        &mut crate::enemy::EnemyState,
+        &mut crate::character_controller::CharacterController,
+    )>,
+    mut writer: EventWriter<BossPhaseChanged>,
+) {
+    for (boss, boss_stats, health, mut phase_index, mut stats, mut enemy_state, mut controller) in
+        boss_query.iter_mut()
+    {
+        // dying isn't a phase
+        if health.current <= 0.0 {
+            continue;
+        }
+        let Some(phase) = boss_stats.phase_at(health.current / health.maximum) else {
+            continue;
+        };
+        // bosses never go back to an earlier phase, even if they're healed
+        if phase_index.0.is_some_and(|current| current >= phase) {
+            continue;
+        }
+
+        info!(
+            "update_boss_phases: {} entered phase {}",
+            boss_stats.name, phase
+        );
+        let first_new = phase_index.0.map_or(0, |current| current + 1);
+        let behavior_changed = boss_stats.phases[first_new..=phase]
+            .iter()
+            .any(|phase| phase.behavior.is_some());
+        *stats = boss_stats.phase_stats(Some(phase));
+        controller.max_speed = stats.speed;
+        if behavior_changed {
+            enemy_state.current = 0;
+            enemy_state.time_in_state = 0.0;
+            enemy_state.patrol_index = 0;
+        }
+
+        let invulnerable = boss_stats.phases[phase].invulnerable;
+        if invulnerable > 0.0 {
+            commands
+                .entity(boss)
+                .insert(Invulnerable(Timer::from_seconds(
+                    invulnerable,
+                    TimerMode::Once,
+                )));
+        }
+
+        phase_index.0 = Some(phase);
+        writer.send(BossPhaseChanged { boss, phase });
+    }
+}
+
+/// Flashes invulnerable enemies, and lets them be hurt again once their time is up
+pub fn flash_invulnerable_bosses(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Invulnerable, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut invulnerable, mut sprite) in query.iter_mut() {
+        invulnerable.0.tick(time.delta());
+        if invulnerable.0.finished() {
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<Invulnerable>();
+            continue;
+        }
+        let lit = (invulnerable.0.elapsed_secs() * FLASH_RATE).fract() < 0.5;
+        sprite.color = if lit {
+            Color::srgb(1.0, 0.9, 0.5)
+        } else {
+            Color::srgba(1.0, 1.0, 1.0, 0.4)
+        };
+    }
+}
@@ -8,7 +8,9 @@ impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            follow_player.run_if(in_state(crate::states::GameState::InGame)),
+            (shake_camera, follow_player)
+                .chain()
+                .run_if(in_state(crate::states::GameState::InGame)),
         );
     }
 }
@@ -19,6 +21,22 @@ pub struct GameCamera;
 #[derive(Debug, Default, Component)]
 pub struct MenuCamera;
 
+/// Shakes the game camera until the timer runs out, less and less as it goes
+#[derive(Debug, Resource)]
+pub struct CameraShake {
+    pub timer: Timer,
+    /// How far the camera moves at first
+    pub strength: f32,
+}
+
+impl CameraShake {
+    fn offset(&self) -> Vec3 {
+        let t = self.timer.elapsed_secs();
+        let fade = self.timer.fraction_remaining();
+        vec3((t * 61.0).sin(), (t * 47.0).cos(), 0.0) * self.strength * fade
+    }
+}
+
 pub fn game_camera() -> impl Bundle {
     (
         Camera2dBundle {
@@ -73,11 +91,36 @@ pub fn destroy_game_camera(mut commands: Commands, query: Query<Entity, With<Gam
     }
 }
 
+/// Starts shaking the camera when a boss changes phase, and stops once the shake is over
+fn shake_camera(
+    mut commands: Commands,
+    mut phase_changes: EventReader<crate::boss::BossPhaseChanged>,
+    shake: Option<ResMut<CameraShake>>,
+    time: Res<Time>,
+) {
+    if phase_changes.read().count() > 0 {
+        commands.insert_resource(CameraShake {
+            timer: Timer::from_seconds(0.6, TimerMode::Once),
+            strength: 6.0,
+        });
+        return;
+    }
+
+    if let Some(mut shake) = shake {
+        shake.timer.tick(time.delta());
+        if shake.timer.finished() {
+            commands.remove_resource::<CameraShake>();
+        }
+    }
+}
+
 fn follow_player(
     player_query: Query<&GlobalTransform, With<crate::player::Player>>,
     mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<GameCamera>>,
     current_room: Option<Res<crate::room::CurrentRoom>>,
+    shake: Option<Res<CameraShake>>,
 ) {
+    let shake_offset = shake.map(|shake| shake.offset()).unwrap_or(Vec3::ZERO);
     let room_info = current_room.as_ref().map(|r| &r.info);
 
     let player_transform = player_query.get_single().ok();
@@ -122,5 +165,6 @@ fn follow_player(
                 camera_transform.translation = vec3(x, y, 0.0);
             }
         }
+        camera_transform.translation += shake_offset;
     }
 }
@@ -6,6 +6,10 @@ pub struct CharacterController {
     pub desired_direction: Vec2,
     pub max_speed: f32,
     pub acceleration: f32,
+    /// Multiplier on `acceleration` from whatever the character is standing on
+    pub acceleration_scale: f32,
+    /// Multiplier on `max_speed` from whatever the character is standing on
+    pub speed_scale: f32,
 }
 
 impl Default for CharacterController {
@@ -14,6 +18,8 @@ impl Default for CharacterController {
             desired_direction: Vec2::ZERO,
             acceleration: 15.0,
             max_speed: 128.0,
+            acceleration_scale: 1.0,
+            speed_scale: 1.0,
         }
     }
 }
@@ -34,10 +40,10 @@ fn accelerate_character_controllers(
 
         let vel = velocity.linvel;
 
-        let desired_velocity = desired_movement * controller.max_speed;
+        let desired_velocity = desired_movement * controller.max_speed * controller.speed_scale;
         let diff = desired_velocity - vel;
 
-        velocity.linvel += diff * controller.acceleration * dt;
+        velocity.linvel += diff * controller.acceleration * controller.acceleration_scale * dt;
     }
 }
 
@@ -25,6 +25,9 @@ impl Plugin for CyclePlugin {
             OnEnter(crate::states::GameState::RestartCycle),
             (
                 reset_init_global_state,
+                resume_cycle,
+                // reads the saved graph seed before `start_cycle` removes `ResumeCycle`
+                crate::room::build_room_graph,
                 crate::save_data::save_data,
                 start_cycle,
             )
@@ -58,27 +61,79 @@ fn start_game(
     }
 
     info!("start_game: Initializing game data from save data");
-    let (player_skills, cycle_counter, muted) = save_data.to_resources();
+    let (player_skills, cycle_counter, muted, story_flags, visited_rooms) =
+        save_data.to_resources();
     commands.insert_resource(player_skills);
     commands.insert_resource(cycle_counter);
     commands.insert_resource(muted);
+    commands.insert_resource(story_flags);
+    commands.insert_resource(visited_rooms);
+    if let Some(cycle_save) = save_data.in_progress.clone() {
+        info!(
+            "start_game: resuming the cycle in progress in {}",
+            cycle_save.room
+        );
+        commands.insert_resource(crate::save_data::ResumeCycle(cycle_save));
+    }
     // remove it so we don't make use of it later when we don't mean to
     commands.remove_resource::<crate::menus::NewGame>();
 }
 
 fn start_cycle(
+    mut commands: Commands,
     mut change_room: EventWriter<crate::room::ChangeRoom>,
     mut unlock_skill: EventWriter<crate::skills::SkillUnlockedEvent>,
+    resume: Option<Res<crate::save_data::ResumeCycle>>,
+    rooms: Res<crate::room::Rooms>,
 ) {
     unlock_skill.send(crate::skills::SkillUnlockedEvent {
         skill: crate::skills::Skill::Armor,
     });
+
+    if let Some(resume) = resume {
+        commands.remove_resource::<crate::save_data::ResumeCycle>();
+        if rooms.map.contains_key(&resume.0.room) {
+            change_room.send(crate::room::ChangeRoom {
+                next_room_name: resume.0.room.clone(),
+                arrival: Some(resume.0.entry),
+                ..Default::default()
+            });
+            return;
+        }
+        warn!(
+            "start_cycle: saved room {} doesn't exist anymore, starting from {}",
+            resume.0.room,
+            crate::room::START_ROOM
+        );
+    }
+
     change_room.send(crate::room::ChangeRoom {
-        next_room_name: "Lovely Cottage".into(),
+        next_room_name: crate::room::START_ROOM.into(),
         ..Default::default()
     });
 }
 
+/// Puts back the progress of a saved cycle, after `reset_init_global_state` has set up a fresh one
+fn resume_cycle(
+    resume: Option<Res<crate::save_data::ResumeCycle>>,
+    mut player_skills: ResMut<crate::skills::PlayerSkills>,
+    mut player_health: ResMut<crate::player::PlayerHealth>,
+    mut room_state: ResMut<crate::room::PersistentRoomState>,
+) {
+    let Some(resume) = resume else {
+        return;
+    };
+
+    for progress in resume.0.skills.iter() {
+        player_skills.restore_cycle_progress(progress.skill, progress.levels, progress.xp);
+    }
+    // the restored levels can raise the player's maximum health
+    player_health.maximum = 100.0 * player_skills.max_health();
+    player_health.current = f32::min(resume.0.health, player_health.maximum);
+    player_health.dead = player_health.current <= 0.0;
+    room_state.rooms.clone_from(&resume.0.rooms);
+}
+
 /// reset or initialize various global state
 fn reset_init_global_state(
     mut commands: Commands,
@@ -42,7 +42,11 @@ pub enum DamageEvent {
 
 fn handle_damage_events(
     mut damage_events: EventReader<DamageEvent>,
-    mut enemy_query: Query<(&mut crate::enemy::EnemyHealth, &GlobalTransform)>,
+    mut enemy_query: Query<(
+        &mut crate::enemy::EnemyHealth,
+        &GlobalTransform,
+        Option<&crate::boss::Invulnerable>,
+    )>,
     player_skills: Res<crate::skills::PlayerSkills>,
     mut enemy_death_events: EventWriter<crate::enemy::EnemyDeathEvent>,
     mut damage_blocked_events: EventWriter<DamageBlockedEvent>,
@@ -66,10 +70,14 @@ fn handle_damage_events(
                 }
             }
             DamageEvent::Enemy { entity, damage } => {
-                let Ok((mut enemy_health, enemy_global_transform)) = enemy_query.get_mut(*entity) else {
+                let Ok((mut enemy_health, enemy_global_transform, invulnerable)) = enemy_query.get_mut(*entity) else {
                     warn!("Got damage event for non-existant enemy {:?}", entity);
                     continue;
                 };
+                // bosses can't be hurt while they change phase
+                if invulnerable.is_some() {
+                    continue;
+                }
                 enemy_health.current -= damage;
                 if enemy_health.current <= 0.0 {
                     let enemy_pos = enemy_global_transform.translation().truncate();
@@ -144,7 +152,7 @@ fn resolve_melee_attacks(
         player_impulse.impulse += dir * 300.0 * enemy_mass / player_mass;
         enemy_impulse.impulse -= dir * 300.0 * player_mass / enemy_mass;
 
-        // TODO: scale based on room difficulty
+        // enemy stats are already scaled to the room's difficulty
         damage_events.send(DamageEvent::Player {
             damage: enemy_stats.melee_damage(),
         });
@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+
+#[derive(Debug, Default)]
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_loading_state(
+            LoadingState::new(crate::states::AppState::CoreLoading)
+                .continue_to_state(crate::states::AppState::RoomLoading)
+                .on_failure_continue_to_state(crate::states::AppState::AppClosing)
+                .load_collection::<DifficultyAssets>()
+                .with_dynamic_assets_file::<StandardDynamicAssetCollection>(
+                    "difficulty/difficulty.assets.ron",
+                ),
+        );
+    }
+}
+
+#[derive(Debug, AssetCollection, Resource)]
+pub struct DifficultyAssets {
+    #[asset(key = "curve")]
+    pub curve: Handle<DifficultyCurve>,
+}
+
+/// How a room's level is worked out, and how much tougher enemies get with each level
+#[derive(Debug, Clone, Asset, Reflect, serde::Deserialize)]
+pub struct DifficultyCurve {
+    /// Levels gained for each room between a room and the start of the cycle
+    pub levels_per_room: f32,
+    /// Levels gained with each cycle completed
+    pub levels_per_cycle: f32,
+    /// Stat multipliers at given levels, sorted by level.
+    /// Levels in between are interpolated, and levels outside are clamped to the ends
+    pub points: Vec<DifficultyPoint>,
+}
+
+#[derive(Debug, Clone, Copy, Reflect, serde::Deserialize)]
+pub struct DifficultyPoint {
+    pub level: u64,
+    /// Multiplier on enemy health
+    pub health: f32,
+    /// Multiplier on melee and projectile damage
+    pub damage: f32,
+    /// Multiplier on movement speed
+    pub speed: f32,
+    /// Multiplier on projectile speed
+    pub projectile_speed: f32,
+}
+
+impl DifficultyPoint {
+    /// Leaves stats as they are
+    pub const IDENTITY: DifficultyPoint = DifficultyPoint {
+        level: 0,
+        health: 1.0,
+        damage: 1.0,
+        speed: 1.0,
+        projectile_speed: 1.0,
+    };
+
+    fn lerp(self, other: DifficultyPoint, t: f32) -> DifficultyPoint {
+        DifficultyPoint {
+            level: self.level,
+            health: self.health.lerp(other.health, t),
+            damage: self.damage.lerp(other.damage, t),
+            speed: self.speed.lerp(other.speed, t),
+            projectile_speed: self.projectile_speed.lerp(other.projectile_speed, t),
+        }
+    }
+}
+
+impl DifficultyCurve {
+    /// The level of a room. `distance` is how many rooms away from the start it is
+    pub fn room_level(&self, fixed_level: Option<u64>, distance: usize, cycle: u64) -> u64 {
+        if let Some(level) = fixed_level {
+            return level;
+        }
+        (distance as f32 * self.levels_per_room + cycle as f32 * self.levels_per_cycle).floor()
+            as u64
+    }
+
+    /// The stat multipliers at the given level
+    pub fn at(&self, level: u64) -> DifficultyPoint {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return DifficultyPoint::IDENTITY;
+        };
+        if level <= first.level {
+            return DifficultyPoint { level, ..*first };
+        }
+        if level >= last.level {
+            return DifficultyPoint { level, ..*last };
+        }
+
+        let next = self
+            .points
+            .iter()
+            .position(|point| point.level > level)
+            .expect("level is below the last point");
+        let (lo, hi) = (self.points[next - 1], self.points[next]);
+        let t = (level - lo.level) as f32 / (hi.level - lo.level) as f32;
+        DifficultyPoint {
+            level,
+            ..lo.lerp(hi, t)
+        }
+    }
+}
+
+impl crate::enemy::EnemyStats {
+    /// A copy of these stats scaled for a room of the given difficulty
+    pub fn scaled(&self, difficulty: &DifficultyPoint) -> crate::enemy::EnemyStats {
+        crate::enemy::EnemyStats {
+            enemy_type: self.enemy_type.scaled(difficulty),
+            health: self.health * difficulty.health,
+            speed: self.speed * difficulty.speed,
+            ..self.clone()
+        }
+    }
+}
+
+impl crate::enemy::EnemyType {
+    /// A copy of this enemy type with its damage and projectiles scaled for a room of the
+    /// given difficulty
+    pub fn scaled(&self, difficulty: &DifficultyPoint) -> crate::enemy::EnemyType {
+        match *self {
+            crate::enemy::EnemyType::Melee { melee_damage } => crate::enemy::EnemyType::Melee {
+                melee_damage: melee_damage * difficulty.damage,
+            },
+            crate::enemy::EnemyType::Ranged {
+                melee_damage,
+                projectile_damage,
+                projectile_speed,
+                projectile_lifetime,
+                homing,
+                delay,
+                pattern,
+            } => crate::enemy::EnemyType::Ranged {
+                melee_damage: melee_damage * difficulty.damage,
+                projectile_damage: projectile_damage * difficulty.damage,
+                projectile_speed: projectile_speed * difficulty.projectile_speed,
+                projectile_lifetime,
+                homing,
+                delay,
+                pattern,
+            },
+        }
+    }
+}
+
+impl crate::enemy::BossStats {
+    /// A copy of these stats, phases included, scaled for a room of the given difficulty
+    pub fn scaled(&self, difficulty: &DifficultyPoint) -> crate::enemy::BossStats {
+        let phases = self
+            .phases
+            .iter()
+            .map(|phase| crate::boss::BossPhase {
+                enemy_type: phase.enemy_type.map(|ty| ty.scaled(difficulty)),
+                speed: phase.speed.map(|speed| speed * difficulty.speed),
+                ..phase.clone()
+            })
+            .collect();
+        crate::enemy::BossStats {
+            stats: self.stats.scaled(difficulty),
+            phases,
+            ..self.clone()
+        }
+    }
+}
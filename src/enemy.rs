@@ -7,12 +7,17 @@ impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<EnemyAlertEvent>()
             .add_event::<EnemyDeathEvent>()
+            .add_event::<crate::boss::BossPhaseChanged>()
             .add_event::<FinalBossDeadEvent>()
             .add_systems(
                 Update,
                 (
-                    move_enemies,
-                    alert_enemies,
+                    (move_enemies, crate::steering::steer_enemies).chain(),
+                    crate::behavior::update_behavior,
+                    crate::behavior::propagate_alerts,
+                    crate::behavior::hear_noises,
+                    crate::boss::update_boss_phases,
+                    crate::boss::flash_invulnerable_bosses,
                     alert_visual,
                     handle_enemy_death,
                     handle_final_boss_death,
@@ -28,10 +33,16 @@ pub struct BossStats {
     pub name: String,
     /// Which skill, if any, is unlocked by defeating this boss
     pub skill_unlocked: Option<crate::skills::Skill>,
+    /// Story flag raised by defeating this boss, see `ExitRequirement::StoryFlag`
+    #[serde(default)]
+    pub story_flag: Option<String>,
     /// scalar on the boss's size
     pub scale: f32,
     /// the rest of the stats
     pub stats: EnemyStats,
+    /// Stages of the fight, from the highest health threshold down, see `BossPhase`
+    #[serde(default)]
+    pub phases: Vec<crate::boss::BossPhase>,
 }
 
 #[derive(Debug, Clone, Component, Asset, Reflect, serde::Deserialize)]
@@ -39,7 +50,7 @@ pub struct EnemyStats {
     /// Type of enemy; either Melee or Ranged
     pub enemy_type: EnemyType,
 
-    /// Base health. will be multiplied by a per-room difficulty scalar
+    /// Base health. will be multiplied by a per-room difficulty scalar, see `DifficultyCurve`
     pub health: f32,
     /// movement speed
     pub speed: f32,
@@ -52,6 +63,41 @@ pub struct EnemyStats {
     pub chase_radius: f32,
     /// How far the enemy will try to stay from the player.
     pub desired_distance: f32,
+
+    /// If given, the enemy only notices the player inside this cone. Without one, it sees all around itself
+    #[serde(default)]
+    pub view_cone: Option<ViewCone>,
+    /// How long the enemy searches where it last saw the player before going back to wandering
+    #[serde(default = "default_search_time")]
+    pub search_time: f32,
+    /// When the enemy spots the player, it warns allies this close. Bosses warn the whole room
+    #[serde(default = "default_shout_radius")]
+    pub shout_radius: f32,
+    /// If given, the enemy hears fighting and deaths this close, see `Condition::HeardNoise`
+    #[serde(default)]
+    pub hearing_radius: Option<f32>,
+    /// What the enemy does. Without one, it wanders, chases and searches, see `Behavior::default_for`
+    #[serde(default)]
+    pub behavior: Option<crate::behavior::Behavior>,
+    /// How the enemy keeps out of its allies' way
+    #[serde(default)]
+    pub steering: crate::steering::Steering,
+}
+
+fn default_search_time() -> f32 {
+    3.0
+}
+
+fn default_shout_radius() -> f32 {
+    150.0
+}
+
+#[derive(Debug, Clone, Copy, Reflect, serde::Deserialize)]
+pub struct ViewCone {
+    /// How wide the cone is, in degrees
+    pub angle: f32,
+    /// The way the enemy faces when it spawns. After that, it faces the way it's moving
+    pub facing: Vec2,
 }
 
 impl EnemyStats {
@@ -63,7 +109,7 @@ impl EnemyStats {
     }
 }
 
-#[derive(Debug, Component, Clone, Copy, Reflect, serde::Deserialize)]
+#[derive(Debug, Component, Clone, Copy, PartialEq, Reflect, serde::Deserialize)]
 pub enum EnemyType {
     Melee {
         melee_damage: f32,
@@ -75,6 +121,9 @@ pub enum EnemyType {
         projectile_lifetime: f32,
         homing: bool,
         delay: f32,
+        /// How each volley is fired. Without one, it's a single projectile straight at the player
+        #[serde(default)]
+        pattern: crate::projectiles::ProjectilePattern,
     },
 }
 
@@ -108,11 +157,36 @@ impl EnemyHealth {
     }
 }
 
-#[derive(Debug, Default, Component)]
-pub enum EnemyState {
-    #[default]
-    Wander,
-    Chase,
+/// Where an enemy is in its `Behavior`
+#[derive(Debug, Default, Clone, Copy, Component, serde::Deserialize, serde::Serialize)]
+pub struct EnemyState {
+    /// Index of the current state in `Behavior::states`
+    pub current: usize,
+    /// Seconds spent in the current state
+    pub time_in_state: f32,
+    /// Where the enemy spawned
+    pub home: Vec2,
+    /// Where the enemy last saw the player
+    pub last_seen: Option<Vec2>,
+    /// Which of its patrol points the enemy is heading for
+    pub patrol_index: usize,
+    /// A warning or a noise the enemy has noticed since its behavior last updated
+    #[serde(skip)]
+    pub heard: Option<AlertCause>,
+}
+
+/// The way an enemy is looking, see `ViewCone`
+#[derive(Debug, Component)]
+pub struct Facing(pub Vec2);
+
+impl Facing {
+    pub fn new(stats: &EnemyStats) -> Self {
+        let facing = stats
+            .view_cone
+            .map(|cone| cone.facing.normalize_or(Vec2::NEG_Y))
+            .unwrap_or(Vec2::NEG_Y);
+        Facing(facing)
+    }
 }
 
 #[derive(Debug, Component)]
@@ -143,16 +217,22 @@ impl WanderState {
     }
 }
 
+/// An enemy has noticed the player
 #[derive(Debug, Clone, Event)]
 pub struct EnemyAlertEvent {
     pub enemy: Entity,
-    pub ty: EnemyAlertEventType,
+    pub cause: AlertCause,
 }
 
-#[derive(Debug, Clone)]
-pub enum EnemyAlertEventType {
-    Alert,
-    TooFar,
+/// How an enemy found out about the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertCause {
+    /// It saw them itself
+    Sight,
+    /// An ally that saw them shouted a warning
+    Shout,
+    /// It heard fighting nearby
+    Noise,
 }
 
 #[derive(Debug, Default, Event, Clone)]
@@ -188,7 +268,7 @@ fn handle_enemy_death(
     mut events: EventReader<EnemyDeathEvent>,
     enemy_query: Query<(
         &GlobalTransform,
-        &crate::room::SpawnerIndex,
+        Option<&crate::room::SpawnerIndex>,
         &Sprite,
         &Handle<Image>,
         Option<&FinalBoss>,
@@ -197,6 +277,7 @@ fn handle_enemy_death(
     current_room: Res<crate::room::CurrentRoom>,
     mut room_state: ResMut<crate::room::PersistentRoomState>,
     mut skill_unlocks: EventWriter<crate::skills::SkillUnlockedEvent>,
+    mut story_flags: ResMut<crate::room::StoryFlags>,
     mut final_boss_dead_event: EventWriter<FinalBossDeadEvent>,
 ) {
     let Some(current_room_state) = room_state.rooms.get_mut(&current_room.info.name) else {
@@ -226,10 +307,18 @@ fn handle_enemy_death(
             if let Some(skill) = boss_stats.skill_unlocked {
                 skill_unlocks.send(crate::skills::SkillUnlockedEvent { skill });
             }
+            if let Some(flag) = boss_stats.story_flag.as_ref() {
+                info!("handle_enemy_death: raising story flag {flag:?}");
+                story_flags.flags.insert(flag.clone());
+            }
         }
 
-        // set the persistent state so this enemy won't spawn anymore for this cycle
-        current_room_state.spawners[spawner_index.0].active = false;
+        // set the persistent state so this enemy won't spawn anymore for this cycle.
+        // Summoned enemies don't have a spawner to switch off
+        if let Some(spawner_index) = spawner_index {
+            current_room_state.spawners[spawner_index.0].active = false;
+            current_room_state.spawners[spawner_index.0].snapshot = None;
+        }
 
         // despawn the enemy
         commands.entity(*entity).despawn_recursive();
@@ -252,17 +341,12 @@ fn handle_enemy_death(
 fn alert_visual(mut commands: Commands, mut events: EventReader<EnemyAlertEvent>) {
     const OFFSET: Vec3 = bevy_math::vec3(0.0, 16.0, 0.0);
     const VELOCITY: Vec2 = bevy_math::vec2(0.0, 8.0);
-    for EnemyAlertEvent { enemy, ty } in events.read() {
-        match ty {
-            EnemyAlertEventType::TooFar => {
-                // do nothing
-                continue;
-            }
-            EnemyAlertEventType::Alert => {
-                // go on...
-            }
-        }
-
+    for EnemyAlertEvent { enemy, cause } in events.read() {
+        let (text, color) = match cause {
+            AlertCause::Sight => ("!", bevy::color::palettes::basic::YELLOW),
+            AlertCause::Shout => ("!", bevy::color::palettes::css::ORANGE),
+            AlertCause::Noise => ("?", bevy::color::palettes::basic::SILVER),
+        };
         let floating_text = commands
             .spawn((
                 SpatialBundle {
@@ -270,10 +354,10 @@ fn alert_visual(mut commands: Commands, mut events: EventReader<EnemyAlertEvent>
                     ..Default::default()
                 },
                 crate::text::TextMarker {
-                    color: Some(bevy::color::palettes::basic::YELLOW.into()),
+                    color: Some(color.into()),
                     fancy: false,
                     font_size: 18.0,
-                    text: "!".to_string(),
+                    text: text.to_string(),
                     ..Default::default()
                 },
                 crate::text::FloatingText {
@@ -288,73 +372,52 @@ fn alert_visual(mut commands: Commands, mut events: EventReader<EnemyAlertEvent>
     }
 }
 
-fn alert_enemies(
-    mut enemy_query: Query<(Entity, &mut EnemyState, &EnemyStats, &GlobalTransform), With<Enemy>>,
-    player_query: Query<&GlobalTransform, With<crate::player::Player>>,
-    mut writer: EventWriter<EnemyAlertEvent>,
-) {
-    let Some(player_pos) = player_query
-        .iter()
-        .next()
-        .map(|t| t.translation().truncate())
-    else {
-        warn!("no player entity");
-        return;
-    };
-    for (enemy, mut enemy_state, enemy_stats, enemy_transform) in enemy_query.iter_mut() {
-        let enemy_pos = enemy_transform.translation().truncate();
-
-        let sqr_dist = player_pos.distance_squared(enemy_pos);
-
-        match *enemy_state {
-            EnemyState::Wander
-                if sqr_dist <= enemy_stats.alert_radius * enemy_stats.alert_radius =>
-            {
-                *enemy_state = EnemyState::Chase;
-                writer.send(EnemyAlertEvent {
-                    enemy,
-                    ty: EnemyAlertEventType::Alert,
-                });
-            }
-            EnemyState::Chase if sqr_dist > enemy_stats.chase_radius * enemy_stats.chase_radius => {
-                *enemy_state = EnemyState::Wander;
-                writer.send(EnemyAlertEvent {
-                    enemy,
-                    ty: EnemyAlertEventType::TooFar,
-                });
-            }
-            _ => {
-                continue;
-            }
-        }
-    }
-}
+/// Everything `move_enemies` needs to pick where an enemy goes
+type EnemyMovement<'a> = (
+    &'a EnemyState,
+    &'a EnemyStats,
+    &'a GlobalTransform,
+    &'a mut crate::character_controller::CharacterController,
+    &'a mut WanderState,
+    &'a mut Facing,
+    &'a crate::room::NavPath,
+);
 
 fn move_enemies(
-    mut query: Query<
-        (
-            &EnemyState,
-            &EnemyStats,
-            &GlobalTransform,
-            &mut crate::character_controller::CharacterController,
-            &mut WanderState,
-        ),
-        With<Enemy>,
-    >,
+    mut query: Query<EnemyMovement, With<Enemy>>,
     player_query: Query<&GlobalTransform, With<crate::player::Player>>,
     time: Res<Time>,
     mut rng: ResMut<crate::rand::GlobalRng>,
 ) {
-    const CLOSE_ENOUGH: f32 = 16.0;
+    use crate::behavior::{Action, CLOSE_ENOUGH};
     let player_pos = player_query
         .iter()
         .next()
         .map(|t| t.translation().truncate());
 
-    for (enemy_state, stats, transform, mut controller, mut wander_state) in query.iter_mut() {
+    for (enemy_state, stats, transform, mut controller, mut wander_state, mut facing, nav_path) in
+        query.iter_mut()
+    {
         let enemy_pos = transform.translation().truncate();
-        match enemy_state {
-            EnemyState::Wander => {
+        let Some(state) = stats.behavior_state(enemy_state) else {
+            controller.desired_direction = Vec2::ZERO;
+            continue;
+        };
+        // head for `target`, going around obstacles on the way, see `NavGrid`
+        let mut go_to = |target: Vec2, speed: f32| {
+            let toward = nav_path.next_waypoint().unwrap_or(target);
+            facing.0 = (toward - enemy_pos).normalize_or(facing.0);
+            controller.desired_direction = (toward - enemy_pos).clamp_length_max(1.0) * speed;
+        };
+        match &state.action {
+            Action::Idle => {
+                controller.desired_direction = Vec2::ZERO;
+            }
+            Action::Patrol { points } => match points.get(enemy_state.patrol_index) {
+                Some(point) => go_to(enemy_state.home + *point, 0.5),
+                None => controller.desired_direction = Vec2::ZERO,
+            },
+            Action::Wander => {
                 wander_state.timer.tick(time.delta());
                 if wander_state.timer.finished() {
                     let new_target = Circle::new(75.0).sample_interior(rng.as_mut());
@@ -363,6 +426,7 @@ fn move_enemies(
                 }
 
                 if let Some(target) = wander_state.target {
+                    facing.0 = (target - enemy_pos).normalize_or(facing.0);
                     if target.distance(enemy_pos) < CLOSE_ENOUGH {
                         controller.desired_direction = Vec2::ZERO;
                         continue;
@@ -372,26 +436,64 @@ fn move_enemies(
                     controller.desired_direction = Vec2::ZERO;
                 }
             }
-            EnemyState::Chase => {
+            Action::Chase | Action::Kite { .. } => {
                 let Some(player_pos) = player_pos else {
                     // no player position, so no need to move
                     controller.desired_direction = Vec2::ZERO;
                     continue;
                 };
-
+                let desired_distance = match state.action {
+                    Action::Kite { distance } => distance,
+                    _ => stats.desired_distance,
+                };
                 let actual_distance = player_pos.distance(enemy_pos);
-                let desired_distance = stats.desired_distance;
 
                 // move toward the player if the actual distance is greater than the desired distance,
-                // and away if the actual distance is less than the desired distance
-                let dir = (player_pos - enemy_pos).clamp_length_max(1.0)
-                    * f32::signum(actual_distance - desired_distance);
-                if (actual_distance - desired_distance).abs() < CLOSE_ENOUGH {
+                // and away if the actual distance is less than the desired distance.
+                if actual_distance > desired_distance + CLOSE_ENOUGH {
+                    go_to(player_pos, 1.0);
+                    continue;
+                }
+                facing.0 = (player_pos - enemy_pos).normalize_or(facing.0);
+                let away = (enemy_pos - player_pos).clamp_length_max(1.0);
+                controller.desired_direction = if actual_distance < desired_distance - CLOSE_ENOUGH
+                {
+                    away
+                } else if matches!(state.action, Action::Kite { .. }) {
+                    // circle around the player
+                    away.perp()
+                } else {
+                    Vec2::ZERO
+                };
+            }
+            Action::Flee => {
+                let Some(player_pos) = player_pos else {
                     controller.desired_direction = Vec2::ZERO;
                     continue;
+                };
+                let away = (enemy_pos - player_pos).normalize_or_zero();
+                facing.0 = away;
+                controller.desired_direction = away;
+            }
+            Action::Search => {
+                let Some(last_seen) = enemy_state.last_seen else {
+                    controller.desired_direction = Vec2::ZERO;
+                    continue;
+                };
+                // look around once it gets there, until it gives up
+                if last_seen.distance(enemy_pos) < CLOSE_ENOUGH {
+                    controller.desired_direction = Vec2::ZERO;
+                    facing.0 = Vec2::from_angle(2.0 * time.delta_seconds()).rotate(facing.0);
+                    continue;
                 }
-
-                controller.desired_direction = dir;
+                go_to(last_seen, 1.0);
+            }
+            Action::ReturnHome => {
+                if enemy_state.home.distance(enemy_pos) < CLOSE_ENOUGH {
+                    controller.desired_direction = Vec2::ZERO;
+                    continue;
+                }
+                go_to(enemy_state.home, 1.0);
             }
         }
     }
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use thiserror::Error;
+
+#[derive(Debug, Default)]
+pub struct ErrorPlugin;
+
+impl Plugin for ErrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GameError>()
+            .add_systems(Startup, spawn_error_overlay)
+            // after everything that sends errors, so a state change lands before the next `Update`
+            .add_systems(PostUpdate, handle_game_errors)
+            .add_systems(Update, expire_error_messages);
+    }
+}
+
+/// Something went wrong that the game can carry on from. Send one of these as an event
+/// instead of panicking
+#[derive(Debug, Clone, Event, Error)]
+pub enum GameError {
+    #[error("room `{0}` isn't loaded")]
+    UnknownRoom(String),
+    #[error("the difficulty curve isn't loaded")]
+    MissingDifficultyCurve,
+    #[error("{room}: enemy stats `{path}` aren't loaded")]
+    MissingEnemyStats { room: String, path: String },
+    #[error("{room}: boss stats aren't loaded")]
+    MissingBossStats { room: String },
+    #[error("couldn't find the player")]
+    MissingPlayer,
+}
+
+/// How the game gets going again after an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Recovery {
+    /// Carry on in the current room. Outside of a room, go back to the main menu
+    StayInRoom,
+    RestartCycle,
+}
+
+impl GameError {
+    fn recovery(&self) -> Recovery {
+        match self {
+            GameError::UnknownRoom(_)
+            | GameError::MissingDifficultyCurve
+            | GameError::MissingEnemyStats { .. }
+            | GameError::MissingBossStats { .. } => Recovery::StayInRoom,
+            GameError::MissingPlayer => Recovery::RestartCycle,
+        }
+    }
+}
+
+/// Holds the error messages currently on screen
+#[derive(Debug, Default, Component)]
+struct ErrorOverlay;
+
+#[derive(Debug, Component)]
+struct ErrorMessage {
+    timer: Timer,
+}
+
+fn spawn_error_overlay(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(16.0),
+                left: Val::Px(16.0),
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            // above every menu
+            z_index: ZIndex::Global(i32::MAX),
+            ..Default::default()
+        },
+        ErrorOverlay,
+        Name::new("Error Overlay"),
+    ));
+}
+
+fn handle_game_errors(
+    mut commands: Commands,
+    mut errors: EventReader<GameError>,
+    overlay_query: Query<Entity, With<ErrorOverlay>>,
+    fonts: Option<Res<crate::text::Fonts>>,
+    state: Res<State<crate::states::GameState>>,
+    mut next_state: ResMut<NextState<crate::states::GameState>>,
+) {
+    for error in errors.read() {
+        error!("handle_game_errors: {error}");
+
+        if let Ok(overlay) = overlay_query.get_single() {
+            let message = commands
+                .spawn((
+                    TextBundle::from_section(
+                        format!("Error: {error}"),
+                        TextStyle {
+                            font: fonts
+                                .as_ref()
+                                .map(|fonts| fonts.normal.clone())
+                                .unwrap_or_default(),
+                            font_size: 24.0,
+                            color: bevy::color::palettes::css::ORANGE_RED.into(),
+                        },
+                    ),
+                    ErrorMessage {
+                        timer: Timer::from_seconds(8.0, TimerMode::Once),
+                    },
+                    Name::new("Error Message"),
+                ))
+                .id();
+            commands.entity(overlay).add_child(message);
+        }
+
+        match error.recovery() {
+            Recovery::StayInRoom => match state.get() {
+                crate::states::GameState::InGame | crate::states::GameState::MainMenu => {}
+                _ => {
+                    warn!("handle_game_errors: not in a room, going back to the main menu");
+                    next_state.set(crate::states::GameState::MainMenu);
+                }
+            },
+            Recovery::RestartCycle => {
+                warn!("handle_game_errors: restarting the cycle");
+                next_state.set(crate::states::GameState::RestartCycle);
+            }
+        }
+    }
+}
+
+fn expire_error_messages(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ErrorMessage)>,
+    time: Res<Time>,
+) {
+    for (entity, mut message) in query.iter_mut() {
+        message.timer.tick(time.delta());
+        if message.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
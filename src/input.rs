@@ -36,6 +36,7 @@ impl PlayerAction {
 #[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
 pub enum MenuAction {
     SkillsMenu,
+    WorldMap,
     MuteSounds,
 }
 
@@ -45,6 +46,7 @@ impl MenuAction {
             (MenuAction::SkillsMenu, KeyCode::Space),
             (MenuAction::SkillsMenu, KeyCode::Tab),
             (MenuAction::SkillsMenu, KeyCode::KeyI),
+            (MenuAction::WorldMap, KeyCode::KeyN),
             (MenuAction::MuteSounds, KeyCode::KeyM),
         ])
     }
@@ -1,14 +1,22 @@
+// bevy systems often need lots of parameters
+#![allow(clippy::too_many_arguments)]
+
 use bevy::prelude::*;
 
 mod assets;
 mod audio;
+mod behavior;
+mod boss;
 mod camera;
 mod character_controller;
 mod cycles;
 mod damage;
+mod difficulty;
 mod enemy;
+mod error;
 mod healthbars;
 mod input;
+mod map;
 mod menus;
 mod physics;
 mod player;
@@ -18,10 +26,23 @@ mod room;
 mod save_data;
 mod skills;
 mod states;
+mod steering;
 mod text;
 mod util;
+#[cfg(not(target_arch = "wasm32"))]
+mod validate;
 
 fn main() {
+    // check the content files without starting the game
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mut args = std::env::args().skip_while(|arg| arg != "--validate");
+        if args.next().is_some() {
+            let assets_dir = args.next().unwrap_or_else(|| "assets".to_string());
+            std::process::exit(validate::validate_content(assets_dir));
+        }
+    }
+
     let mut app = App::new();
 
     #[cfg(target_arch = "wasm32")]
@@ -42,13 +63,16 @@ fn main() {
     .add_plugins(input::InputPlugin)
     .add_plugins(player::PlayerPlugin)
     .add_plugins(enemy::EnemyPlugin)
+    .add_plugins(error::ErrorPlugin)
     .add_plugins(damage::DamagePlugin)
     .add_plugins(room::RoomPlugin)
+    .add_plugins(map::MapPlugin)
     .add_plugins(text::TextPlugin)
     .add_plugins(menus::MenusPlugin)
     .add_plugins(camera::CameraPlugin)
     .add_plugins(skills::SkillsPlugin)
     .add_plugins(cycles::CyclePlugin)
+    .add_plugins(difficulty::DifficultyPlugin)
     .add_plugins(healthbars::HealthbarsPlugin)
     .add_plugins(projectiles::ProjectilesPlugin)
     .add_plugins(audio::AudioPlugin)
@@ -0,0 +1,467 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_math::vec2;
+
+#[derive(Debug, Default)]
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VisitedRooms>()
+            // `CurrentRoom` is the room being entered by now
+            .add_systems(
+                OnEnter(crate::states::GameState::RoomTransition),
+                mark_room_visited,
+            )
+            .add_systems(OnEnter(crate::states::GameState::InGame), spawn_minimap)
+            .add_systems(
+                Update,
+                update_minimap.run_if(in_state(crate::states::GameState::InGame)),
+            )
+            .add_systems(OnEnter(crate::states::MenuState::WorldMap), world_map);
+    }
+}
+
+/// Every room that's ever been visited, across all cycles.
+/// Rooms visited this cycle are the ones in `PersistentRoomState`
+#[derive(Debug, Default, Resource)]
+pub struct VisitedRooms {
+    pub rooms: HashSet<String>,
+}
+
+fn mark_room_visited(
+    current_room: Res<crate::room::CurrentRoom>,
+    mut visited: ResMut<VisitedRooms>,
+) {
+    if !visited.rooms.contains(&current_room.info.name) {
+        info!(
+            "mark_room_visited: first visit to {}",
+            current_room.info.name
+        );
+        visited.rooms.insert(current_room.info.name.clone());
+    }
+}
+
+/// Longest side of the minimap, in pixels
+const MINIMAP_SIZE: f32 = 180.0;
+
+#[derive(Debug, Default, Component)]
+struct Minimap;
+
+/// Shows where something in the room is on the minimap
+#[derive(Debug, Component)]
+struct MinimapIcon {
+    target: Entity,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IconStyle {
+    size: f32,
+    color: Color,
+    /// Drawn above icons with a lower number
+    z: i32,
+}
+
+const OBSTACLE_ICON: IconStyle = IconStyle {
+    size: 6.0,
+    color: Color::srgb(0.5, 0.5, 0.5),
+    z: 0,
+};
+const ENEMY_ICON: IconStyle = IconStyle {
+    size: 6.0,
+    color: Color::srgb(1.0, 0.0, 0.0),
+    z: 1,
+};
+const BOSS_ICON: IconStyle = IconStyle {
+    size: 10.0,
+    color: Color::srgb(0.5, 0.0, 0.5),
+    z: 2,
+};
+const PLAYER_ICON: IconStyle = IconStyle {
+    size: 8.0,
+    color: Color::srgb(0.53, 0.81, 0.92),
+    z: 3,
+};
+
+fn spawn_minimap(mut commands: Commands, current_room: Option<Res<crate::room::CurrentRoom>>) {
+    let Some(current_room) = current_room else {
+        return;
+    };
+    // same shape as the room
+    let size = current_room.info.rect.size();
+    let scale = MINIMAP_SIZE / f32::max(size.x, size.y);
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(16.0),
+                right: Val::Px(16.0),
+                width: Val::Px(size.x * scale),
+                height: Val::Px(size.y * scale),
+                border: UiRect::all(Val::Px(2.0)),
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.0, 0.0, 0.0, 0.5).into(),
+            border_color: bevy::color::palettes::basic::WHITE.into(),
+            ..Default::default()
+        },
+        Minimap,
+        StateScoped(crate::states::GameState::InGame),
+        Name::new("Minimap"),
+    ));
+}
+
+fn update_minimap(
+    mut commands: Commands,
+    minimap_query: Query<Entity, With<Minimap>>,
+    mut icon_query: Query<(Entity, &MinimapIcon, &mut Style)>,
+    player_query: Query<(Entity, &GlobalTransform), With<crate::player::Player>>,
+    enemy_query: Query<
+        (Entity, &GlobalTransform, Has<crate::enemy::Boss>),
+        With<crate::enemy::Enemy>,
+    >,
+    obstacle_query: Query<(Entity, &GlobalTransform), With<crate::room::Obstacle>>,
+    current_room: Res<crate::room::CurrentRoom>,
+) {
+    let Ok(minimap) = minimap_query.get_single() else {
+        return;
+    };
+
+    let mut targets: HashMap<Entity, (Vec2, IconStyle)> = HashMap::new();
+    for (entity, transform) in obstacle_query.iter() {
+        targets.insert(entity, (transform.translation().truncate(), OBSTACLE_ICON));
+    }
+    for (entity, transform, is_boss) in enemy_query.iter() {
+        let icon = if is_boss { BOSS_ICON } else { ENEMY_ICON };
+        targets.insert(entity, (transform.translation().truncate(), icon));
+    }
+    for (entity, transform) in player_query.iter() {
+        targets.insert(entity, (transform.translation().truncate(), PLAYER_ICON));
+    }
+
+    let rect = current_room.info.rect;
+    let place = |style: &mut Style, pos: Vec2| {
+        // y points down in the UI
+        let fraction = ((pos - rect.min) / rect.size()).clamp(Vec2::ZERO, Vec2::ONE);
+        style.left = Val::Percent(fraction.x * 100.0);
+        style.top = Val::Percent((1.0 - fraction.y) * 100.0);
+    };
+
+    for (entity, icon, mut style) in icon_query.iter_mut() {
+        match targets.remove(&icon.target) {
+            Some((pos, _)) => place(&mut style, pos),
+            // dead enemies and the like
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+
+    for (target, (pos, icon)) in targets {
+        let half = Val::Px(-icon.size / 2.0);
+        let mut style = Style {
+            position_type: PositionType::Absolute,
+            width: Val::Px(icon.size),
+            height: Val::Px(icon.size),
+            // center the icon on the position
+            margin: UiRect::new(half, Val::ZERO, half, Val::ZERO),
+            ..Default::default()
+        };
+        place(&mut style, pos);
+        let icon = commands
+            .spawn((
+                NodeBundle {
+                    style,
+                    background_color: icon.color.into(),
+                    z_index: ZIndex::Local(icon.z),
+                    ..Default::default()
+                },
+                MinimapIcon { target },
+                Name::new("Minimap Icon"),
+            ))
+            .id();
+        commands.entity(minimap).add_child(icon);
+    }
+}
+
+/// Places every room reachable from `START_ROOM` on a grid, following the direction of the
+/// exits between them. Rooms that would overlap are moved to the closest free spot
+fn layout_world_map(
+    rooms: &crate::room::Rooms,
+    room_graph: &crate::room::RoomGraph,
+) -> HashMap<String, IVec2> {
+    let mut positions: HashMap<String, IVec2> = HashMap::new();
+    let mut taken: HashSet<IVec2> = HashSet::new();
+    let mut queue = std::collections::VecDeque::new();
+    if rooms.map.contains_key(crate::room::START_ROOM) {
+        positions.insert(crate::room::START_ROOM.to_string(), IVec2::ZERO);
+        taken.insert(IVec2::ZERO);
+        queue.push_back(crate::room::START_ROOM.to_string());
+    }
+
+    while let Some(name) = queue.pop_front() {
+        let Some((info, _)) = rooms.map.get(&name) else {
+            continue;
+        };
+        let pos = positions[&name];
+        for (index, exit) in info.exits.iter().enumerate() {
+            let Some(link) = room_graph.link(&name, index) else {
+                continue;
+            };
+            if positions.contains_key(&link.room) {
+                continue;
+            }
+            let wanted = pos + exit.grid_direction(info.rect);
+            // search outward in rings until there's a free spot
+            let spot = (0..)
+                .flat_map(|radius: i32| {
+                    (-radius..=radius)
+                        .flat_map(move |x| (-radius..=radius).map(move |y| IVec2::new(x, y)))
+                        .filter(move |offset| offset.x.abs().max(offset.y.abs()) == radius)
+                })
+                .map(|offset| wanted + offset)
+                .find(|spot| !taken.contains(spot))
+                .expect("there's always a free spot eventually");
+            taken.insert(spot);
+            positions.insert(link.room.clone(), spot);
+            queue.push_back(link.room.clone());
+        }
+    }
+    positions
+}
+
+fn world_map(
+    mut commands: Commands,
+    rooms: Res<crate::room::Rooms>,
+    room_graph: Res<crate::room::RoomGraph>,
+    room_state: Res<crate::room::PersistentRoomState>,
+    visited: Res<VisitedRooms>,
+    current_room: Option<Res<crate::room::CurrentRoom>>,
+    boss_stats: Res<Assets<crate::enemy::BossStats>>,
+    player_skills: Res<crate::skills::PlayerSkills>,
+    fonts: Res<crate::text::Fonts>,
+) {
+    const CELL: Vec2 = vec2(200.0, 110.0);
+    const ROOM: Vec2 = vec2(160.0, 80.0);
+
+    let positions = layout_world_map(&rooms, &room_graph);
+    // rooms next to a visited room show up, but not what's in them
+    let shown: HashSet<&String> = positions
+        .keys()
+        .filter(|name| {
+            visited.rooms.contains(*name)
+                || room_graph.links.get(*name).is_some_and(|links| {
+                    links
+                        .iter()
+                        .flatten()
+                        .any(|link| visited.rooms.contains(&link.room))
+                })
+        })
+        .collect();
+
+    let Some(min) = shown.iter().map(|name| positions[*name]).reduce(IVec2::min) else {
+        info!("world_map: no rooms visited yet");
+        return;
+    };
+    let max = shown
+        .iter()
+        .map(|name| positions[*name])
+        .fold(min, IVec2::max);
+    // grid y points north, UI y points down
+    let center = |pos: IVec2| {
+        vec2(
+            (pos.x - min.x) as f32 * CELL.x + CELL.x / 2.0,
+            (max.y - pos.y) as f32 * CELL.y + CELL.y / 2.0,
+        )
+    };
+    let text_style = |color: Color| TextStyle {
+        font: fonts.normal.clone(),
+        font_size: 16.0,
+        color,
+    };
+
+    let mut map = commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Px((max.x - min.x + 1) as f32 * CELL.x),
+                height: Val::Px((max.y - min.y + 1) as f32 * CELL.y),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Name::new("World Map Rooms"),
+    ));
+    map.with_children(|parent| {
+        // connections first, so the rooms are drawn over them
+        let mut drawn: HashSet<(&str, &str)> = HashSet::new();
+        for name in shown.iter() {
+            for link in room_graph.links[*name].iter().flatten() {
+                if !shown.contains(&link.room) {
+                    continue;
+                }
+                let pair = if name.as_str() < link.room.as_str() {
+                    (name.as_str(), link.room.as_str())
+                } else {
+                    (link.room.as_str(), name.as_str())
+                };
+                if !drawn.insert(pair) {
+                    continue;
+                }
+                let from = center(positions[*name]);
+                let to = center(positions[&link.room]);
+                let length = from.distance(to);
+                let mid = (from + to) / 2.0;
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(mid.x - length / 2.0),
+                            top: Val::Px(mid.y - 2.0),
+                            width: Val::Px(length),
+                            height: Val::Px(4.0),
+                            ..Default::default()
+                        },
+                        background_color: bevy::color::palettes::tailwind::GRAY_500.into(),
+                        transform: Transform::from_rotation(Quat::from_rotation_z(
+                            (to - from).to_angle(),
+                        )),
+                        ..Default::default()
+                    },
+                    Name::new("World Map Connection"),
+                ));
+            }
+        }
+
+        for name in shown.iter() {
+            let pos = center(positions[*name]) - ROOM / 2.0;
+            let this_cycle = room_state.rooms.get(*name);
+            let is_current = current_room
+                .as_ref()
+                .is_some_and(|current_room| current_room.info.name == **name);
+            let background = if this_cycle.is_some() {
+                bevy::color::palettes::tailwind::GRAY_600
+            } else if visited.rooms.contains(*name) {
+                bevy::color::palettes::tailwind::GRAY_800
+            } else {
+                bevy::color::palettes::tailwind::GRAY_900
+            };
+            let border = if is_current {
+                bevy::color::palettes::css::SKY_BLUE
+            } else {
+                bevy::color::palettes::tailwind::GRAY_400
+            };
+
+            let mut sections = Vec::new();
+            if visited.rooms.contains(*name) {
+                sections.push(TextSection::new(
+                    name.as_str(),
+                    text_style(bevy::color::palettes::basic::WHITE.into()),
+                ));
+                let (info, assets) = &rooms.map[*name];
+                if info.boss {
+                    let defeated = this_cycle.is_some_and(|state| {
+                        state
+                            .spawners
+                            .iter()
+                            .filter(|s| s.ty == crate::room::SpawnerType::Boss)
+                            .all(|s| !s.active)
+                    });
+                    sections.push(if defeated {
+                        TextSection::new(
+                            "\nBoss defeated",
+                            text_style(bevy::color::palettes::tailwind::GREEN_400.into()),
+                        )
+                    } else {
+                        TextSection::new(
+                            "\nBoss",
+                            text_style(bevy::color::palettes::basic::RED.into()),
+                        )
+                    });
+                }
+                let artifact = assets
+                    .boss_stats
+                    .as_ref()
+                    .and_then(|handle| boss_stats.get(handle))
+                    .and_then(|stats| stats.skill_unlocked);
+                if let Some(skill) = artifact {
+                    let color = if player_skills.get_unlocked(skill) {
+                        bevy::color::palettes::tailwind::GRAY_400
+                    } else {
+                        bevy::color::palettes::css::GOLD
+                    };
+                    sections.push(TextSection::new(
+                        format!("\nArtifact: {skill}"),
+                        text_style(color.into()),
+                    ));
+                }
+            } else {
+                sections.push(TextSection::new(
+                    "???",
+                    text_style(bevy::color::palettes::tailwind::GRAY_500.into()),
+                ));
+            }
+
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(pos.x),
+                            top: Val::Px(pos.y),
+                            width: Val::Px(ROOM.x),
+                            height: Val::Px(ROOM.y),
+                            border: UiRect::all(Val::Px(if is_current { 4.0 } else { 2.0 })),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..Default::default()
+                        },
+                        background_color: background.into(),
+                        border_color: border.into(),
+                        ..Default::default()
+                    },
+                    Name::new("World Map Room"),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_sections(sections).with_text_justify(JustifyText::Center),
+                        Name::new("World Map Room Text"),
+                    ));
+                });
+        }
+    });
+    let map = map.id();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Vw(100.0),
+                    height: Val::Vh(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(16.0),
+                    ..Default::default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.85).into(),
+                ..Default::default()
+            },
+            StateScoped(crate::states::MenuState::WorldMap),
+            Name::new("World Map Root"),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "World Map",
+                    TextStyle {
+                        font: fonts.fancy.clone(),
+                        font_size: 48.0,
+                        ..Default::default()
+                    },
+                ),
+                Name::new("World Map Title"),
+            ));
+        })
+        .add_child(map);
+}
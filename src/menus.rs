@@ -37,6 +37,10 @@ impl Plugin for MenusPlugin {
                 OnEnter(crate::states::GameState::RoomTransition),
                 (|| crate::states::GameState::RoomTransition).pipe(loading_screen),
             )
+            .add_systems(
+                Update,
+                update_loading_progress.run_if(in_state(crate::states::AppState::RoomLoading)),
+            )
             .add_systems(OnEnter(crate::states::GameState::MainMenu), main_menu)
             .add_systems(Update, process_button_interactions)
             .add_systems(OnEnter(crate::states::MenuState::SkillsMenu), skills_menu)
@@ -54,7 +58,7 @@ impl Plugin for MenusPlugin {
             )
             .add_systems(
                 Update,
-                open_close_skills_menu.run_if(in_state(crate::states::GameState::InGame)),
+                open_close_menus.run_if(in_state(crate::states::GameState::InGame)),
             );
 
         // Menu systems
@@ -154,6 +158,10 @@ struct ButtonSystem(&'static str);
 #[derive(Debug, Component, Default)]
 struct ButtonPreviousInteraction(Option<Interaction>);
 
+/// Text under the loading button, saying how far along loading is
+#[derive(Debug, Default, Component)]
+struct LoadingProgressText;
+
 /// when inserted, indicates that we want to start a new game, deleting the previous save data
 #[derive(Debug, Default, Resource)]
 pub struct NewGame;
@@ -472,6 +480,7 @@ fn main_menu(
     fonts: Res<crate::text::Fonts>,
     ui_assets: Res<UiAssets>,
     save_data: Option<Res<crate::save_data::SaveData>>,
+    room_load_errors: Res<crate::assets::RoomLoadErrors>,
 ) {
     commands.spawn((
         crate::camera::menu_camera(),
@@ -540,6 +549,35 @@ fn main_menu(
         commands.entity(base).add_child(continue_button);
     }
     commands.entity(base).add_child(new_game_button);
+
+    if !room_load_errors.errors.is_empty() {
+        let mut report = "Some rooms couldn't be loaded and were left out:".to_string();
+        for error in room_load_errors.errors.iter() {
+            report.push('\n');
+            report.push_str(error);
+        }
+        let report = commands
+            .spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        report,
+                        TextStyle {
+                            color: bevy::color::palettes::css::ORANGE_RED.into(),
+                            font: fonts.normal.clone(),
+                            font_size: 24.0,
+                        },
+                    ),
+                    style: Style {
+                        margin: UiRect::top(Val::Px(16.0)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Name::new("Room Load Errors"),
+            ))
+            .id();
+        commands.entity(base).add_child(report);
+    }
 }
 
 fn loading_screen<S: States>(
@@ -557,6 +595,27 @@ fn loading_screen<S: States>(
         fonts.normal.clone(),
         ui_assets.panel.clone(),
     );
+    let progress_text = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: fonts.normal.clone(),
+                        font_size: 24.0,
+                        ..Default::default()
+                    },
+                ),
+                style: Style {
+                    margin: UiRect::top(Val::Px(16.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            LoadingProgressText,
+            Name::new("Loading Progress Text"),
+        ))
+        .id();
 
     commands.spawn((
         SpriteBundle {
@@ -579,6 +638,7 @@ fn loading_screen<S: States>(
                     height: Val::Percent(100.0),
                     align_items: AlignItems::Center,
                     justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
                     ..Default::default()
                 },
                 ..Default::default()
@@ -586,10 +646,27 @@ fn loading_screen<S: States>(
             StateScoped(state.clone()),
             Name::new("Loading Screen Root"),
         ))
-        .add_child(loading_button);
+        .add_child(loading_button)
+        .add_child(progress_text);
 }
 
-fn open_close_skills_menu(
+fn update_loading_progress(
+    loading: Option<Res<crate::assets::LoadingRooms>>,
+    mut query: Query<&mut Text, With<LoadingProgressText>>,
+) {
+    let Some((done, total)) = loading.and_then(|loading| loading.progress()) else {
+        return;
+    };
+    for mut text in query.iter_mut() {
+        let value = format!("Rooms: {done} / {total}");
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+/// Menus opened from the game stay open while their key is held
+fn open_close_menus(
     menu_actions: Res<ActionState<crate::input::MenuAction>>,
     current_state: Res<State<crate::states::MenuState>>,
     mut next_state: ResMut<NextState<crate::states::MenuState>>,
@@ -597,13 +674,22 @@ fn open_close_skills_menu(
     match current_state.get() {
         &crate::states::MenuState::None => {
             if menu_actions.pressed(&crate::input::MenuAction::SkillsMenu) {
-                info!("open_close_skills_menu: opening skills menu");
+                info!("open_close_menus: opening skills menu");
                 next_state.set(crate::states::MenuState::SkillsMenu);
+            } else if menu_actions.pressed(&crate::input::MenuAction::WorldMap) {
+                info!("open_close_menus: opening world map");
+                next_state.set(crate::states::MenuState::WorldMap);
             }
         }
         &crate::states::MenuState::SkillsMenu => {
             if menu_actions.released(&crate::input::MenuAction::SkillsMenu) {
-                info!("open_close_skills_menu: closing skills menu");
+                info!("open_close_menus: closing skills menu");
+                next_state.set(crate::states::MenuState::None);
+            }
+        }
+        &crate::states::MenuState::WorldMap => {
+            if menu_actions.released(&crate::input::MenuAction::WorldMap) {
+                info!("open_close_menus: closing world map");
                 next_state.set(crate::states::MenuState::None);
             }
         }
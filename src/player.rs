@@ -109,6 +109,7 @@ fn handle_player_death(
     mut next_state: ResMut<NextState<crate::states::GameState>>,
     final_boss_dead: Option<Res<crate::enemy::FinalBossDead>>,
     mut player_death_events: EventWriter<PlayerDeathEvent>,
+    mut errors: EventWriter<crate::error::GameError>,
     time: Res<Time>,
 ) {
     if !player_health.dead || final_boss_dead.is_some() {
@@ -125,11 +126,13 @@ fn handle_player_death(
         }
     } else {
         // player just died, do stuff here
-        commands.init_resource::<PlayerDeathTimer>();
-
         let Ok((player_entity, mut transform)) = player_query.get_single_mut() else {
-            panic!("handle_player_death")
+            // nothing to play the death on, so skip straight to the next cycle
+            cycle_counter.count += 1;
+            errors.send(crate::error::GameError::MissingPlayer);
+            return;
         };
+        commands.init_resource::<PlayerDeathTimer>();
 
         player_death_events.send(PlayerDeathEvent {
             pos: transform.translation.truncate(),
@@ -32,8 +32,18 @@ pub struct Projectile {
     pub damage: f32,
     pub homing: bool,
     pub timer: Timer,
+    /// The projectile's sprite, kept around so reflected projectiles look the same
+    pub texture: Handle<Image>,
 }
 
+/// Marks a projectile the player has sent back at its source
+#[derive(Debug, Component)]
+pub struct Reflected;
+
+/// The sprite an enemy's projectiles use
+#[derive(Debug, Clone, Component)]
+pub struct ProjectileSprite(pub Handle<Image>);
+
 #[derive(Debug, Clone, Event)]
 pub struct ProjectileHitEvent {
     pub projectile: Projectile,
@@ -45,6 +55,61 @@ pub struct ProjectileReflectEvent {
     pub projectile: Projectile,
 }
 
+/// How a ranged enemy fires a volley of projectiles each time its `delay` is up
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, serde::Deserialize)]
+#[serde(default)]
+pub struct ProjectilePattern {
+    /// How many projectiles each shot fires at once
+    pub count: usize,
+    /// The angle, in degrees, a shot's projectiles are fanned out over
+    pub spread: f32,
+    /// Fires a shot's projectiles evenly all the way around the enemy, instead of fanning them
+    /// out over `spread`
+    pub ring: bool,
+    /// How many shots make up a volley
+    pub burst: usize,
+    /// Seconds between the shots of a volley
+    pub burst_delay: f32,
+    /// Degrees the aim turns after each shot of a volley, for spirals. The first shot is
+    /// aimed at the player
+    pub spin: f32,
+    /// How much to aim at where the player is heading rather than where they are, from 0 to 1
+    pub lead: f32,
+}
+
+impl Default for ProjectilePattern {
+    fn default() -> Self {
+        ProjectilePattern {
+            count: 1,
+            spread: 0.0,
+            ring: false,
+            burst: 1,
+            burst_delay: 0.1,
+            spin: 0.0,
+            lead: 0.0,
+        }
+    }
+}
+
+impl ProjectilePattern {
+    /// The directions of one shot's projectiles, given which way it's aimed
+    fn directions(&self, aim: f32) -> Vec<Vec2> {
+        let count = self.count.max(1);
+        (0..count)
+            .map(|i| {
+                let offset = if self.ring {
+                    std::f32::consts::TAU * i as f32 / count as f32
+                } else if count == 1 {
+                    0.0
+                } else {
+                    self.spread.to_radians() * (i as f32 / (count - 1) as f32 - 0.5)
+                };
+                Vec2::from_angle(aim + offset)
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Component)]
 struct ProjectileLauncherState {
     timer: Timer,
@@ -53,6 +118,15 @@ struct ProjectileLauncherState {
     projectile_damage: f32,
     projectile_lifetime: f32,
     homing: bool,
+    pattern: ProjectilePattern,
+    /// Shots still to come in the current volley
+    shots_left: usize,
+    /// Counts down to the next shot of the current volley
+    burst_timer: Timer,
+    /// Where the next shot of the current volley is aimed, in radians
+    aim: f32,
+    /// The enemy type the launcher was set up from
+    enemy_type: crate::enemy::EnemyType,
 }
 
 fn spawn_reflected_projectiles(
@@ -61,7 +135,6 @@ fn spawn_reflected_projectiles(
     enemy_query: Query<&GlobalTransform>,
     player_skills: Res<crate::skills::PlayerSkills>,
     mut events: EventReader<ProjectileReflectEvent>,
-    current_room: Res<crate::room::CurrentRoom>,
     mut rng: ResMut<crate::rand::GlobalRng>,
 ) {
     let Ok(player_transform) = player_query.get_single() else {
@@ -84,7 +157,7 @@ fn spawn_reflected_projectiles(
         new_timer.reset();
         commands.spawn((
             SpriteBundle {
-                texture: current_room.assets.projectile.clone(),
+                texture: projectile.texture.clone(),
                 transform: Transform::from_translation(player_pos.extend(-1.0))
                     .with_rotation(Quat::from_rotation_z(initial_angle)),
                 ..Default::default()
@@ -96,12 +169,13 @@ fn spawn_reflected_projectiles(
                 timer: new_timer,
                 ..projectile.clone()
             },
+            Reflected,
             RigidBody::KinematicVelocityBased,
             Collider::ball(8.0),
             Sensor,
             CollisionGroups::new(
                 crate::physics::COLLISION_GROUP_REFLECTED_PROJECTILE,
-                crate::physics::COLLISION_GROUP_ENEMY,
+                crate::physics::COLLISION_GROUP_ENEMY | crate::physics::COLLISION_GROUP_OBSTACLE,
             ),
             Velocity::linear(dir * projectile.speed),
             crate::room::RoomObject,
@@ -215,97 +289,142 @@ fn update_projectiles(
 
 fn launch_projectiles(
     mut commands: Commands,
-    player_query: Query<(Entity, &GlobalTransform), With<crate::player::Player>>,
+    player_query: Query<(Entity, &GlobalTransform, &Velocity), With<crate::player::Player>>,
     mut enemy_query: Query<(
         Entity,
         &GlobalTransform,
-        &crate::enemy::EnemyState,
         &mut ProjectileLauncherState,
+        Option<&ProjectileSprite>,
     )>,
+    behavior_query: Query<(&crate::enemy::EnemyState, &crate::enemy::EnemyStats)>,
     current_room: Res<crate::room::CurrentRoom>,
     time: Res<Time>,
 ) {
-    let Ok((player_entity, player_global_transform)) = player_query.get_single() else {
+    let Ok((player_entity, player_global_transform, player_velocity)) = player_query.get_single()
+    else {
         warn!("launch_projectiles: couldn't get single player");
         return;
     };
     let player_pos = player_global_transform.translation().truncate();
 
-    for (enemy_entity, global_transform, enemy_state, mut projectile_launcher_state) in
+    for (enemy_entity, global_transform, mut projectile_launcher_state, sprite) in
         enemy_query.iter_mut()
     {
-        match enemy_state {
-            crate::enemy::EnemyState::Wander => {
-                // reset the delay on the launcher, that's it
-                projectile_launcher_state.timer.reset();
-                let delay = projectile_launcher_state.delay;
-                projectile_launcher_state
-                    .timer
-                    .set_duration(std::time::Duration::from_secs_f32(delay));
-                continue;
-            }
-            &crate::enemy::EnemyState::Chase => {
-                // go on...
-            }
+        let shoots = behavior_query
+            .get(enemy_entity)
+            .ok()
+            .and_then(|(enemy_state, enemy_stats)| enemy_stats.behavior_state(enemy_state))
+            .is_some_and(|state| state.shoots);
+        if !shoots {
+            // reset the delay on the launcher, that's it
+            projectile_launcher_state.timer.reset();
+            let delay = projectile_launcher_state.delay;
+            projectile_launcher_state
+                .timer
+                .set_duration(std::time::Duration::from_secs_f32(delay));
+            projectile_launcher_state.shots_left = 0;
+            continue;
         }
 
-        projectile_launcher_state.timer.tick(time.delta());
+        let enemy_pos = global_transform.translation().truncate();
+        let pattern = projectile_launcher_state.pattern;
+        if projectile_launcher_state.shots_left == 0 {
+            projectile_launcher_state.timer.tick(time.delta());
+            if !projectile_launcher_state.timer.finished() {
+                continue;
+            }
+            projectile_launcher_state.timer.reset();
 
-        if !projectile_launcher_state.timer.finished() {
-            continue;
+            // start a new volley, aimed at the player, or ahead of them if it leads its shots.
+            // Projectiles that don't move never get there, so there's nothing to lead
+            let mut target = player_pos;
+            if pattern.lead > 0.0 && projectile_launcher_state.projectile_speed > 0.0 {
+                let time_to_hit =
+                    player_pos.distance(enemy_pos) / projectile_launcher_state.projectile_speed;
+                target += player_velocity.linvel * time_to_hit * pattern.lead;
+            }
+            let dir = (target - enemy_pos).normalize_or(Vec2::X);
+            projectile_launcher_state.aim = Vec2::X.angle_between(dir);
+            projectile_launcher_state.shots_left = pattern.burst.max(1);
+        } else {
+            projectile_launcher_state.burst_timer.tick(time.delta());
+            if !projectile_launcher_state.burst_timer.finished() {
+                continue;
+            }
         }
+        projectile_launcher_state.burst_timer =
+            Timer::from_seconds(pattern.burst_delay, TimerMode::Once);
+        projectile_launcher_state.shots_left -= 1;
+        let aim = projectile_launcher_state.aim;
+        projectile_launcher_state.aim += pattern.spin.to_radians();
 
-        projectile_launcher_state.timer.reset();
-
-        let enemy_pos = global_transform.translation().truncate();
-        let dir = (player_pos - enemy_pos).normalize_or(Vec2::X);
-        let initial_angle = Vec2::X.angle_between(dir);
-        commands.spawn((
-            SpriteBundle {
-                texture: current_room.assets.projectile.clone(),
-                transform: Transform::from_translation(enemy_pos.extend(3.0))
-                    .with_rotation(Quat::from_rotation_z(initial_angle)),
-                ..Default::default()
-            },
-            Projectile {
-                source: enemy_entity,
-                target: player_entity,
-                speed: projectile_launcher_state.projectile_speed,
-                damage: projectile_launcher_state.projectile_damage,
-                homing: projectile_launcher_state.homing,
-                timer: Timer::from_seconds(
-                    projectile_launcher_state.projectile_lifetime,
-                    TimerMode::Once,
+        let texture = sprite
+            .map(|sprite| sprite.0.clone())
+            .unwrap_or(current_room.assets.projectile.clone());
+        for dir in pattern.directions(aim) {
+            commands.spawn((
+                SpriteBundle {
+                    texture: texture.clone(),
+                    transform: Transform::from_translation(enemy_pos.extend(3.0))
+                        .with_rotation(Quat::from_rotation_z(Vec2::X.angle_between(dir))),
+                    ..Default::default()
+                },
+                Projectile {
+                    source: enemy_entity,
+                    target: player_entity,
+                    speed: projectile_launcher_state.projectile_speed,
+                    damage: projectile_launcher_state.projectile_damage,
+                    homing: projectile_launcher_state.homing,
+                    timer: Timer::from_seconds(
+                        projectile_launcher_state.projectile_lifetime,
+                        TimerMode::Once,
+                    ),
+                    texture: texture.clone(),
+                },
+                RigidBody::KinematicVelocityBased,
+                Collider::ball(8.0),
+                Sensor,
+                CollisionGroups::new(
+                    crate::physics::COLLISION_GROUP_PROJECTILE,
+                    crate::physics::COLLISION_GROUP_PLAYER,
                 ),
-            },
-            RigidBody::KinematicVelocityBased,
-            Collider::ball(8.0),
-            Sensor,
-            CollisionGroups::new(
-                crate::physics::COLLISION_GROUP_PROJECTILE,
-                crate::physics::COLLISION_GROUP_PLAYER,
-            ),
-            Velocity::linear(dir * projectile_launcher_state.projectile_speed),
-            crate::room::RoomObject,
-            Name::new("Projectile"),
-        ));
+                Velocity::linear(dir * projectile_launcher_state.projectile_speed),
+                crate::room::RoomObject,
+                Name::new("Projectile"),
+            ));
+        }
     }
 }
 
+/// Sets up the launcher of ranged enemies when they spawn, and again whenever their enemy type
+/// changes. Other stat changes leave a volley in progress alone
 fn add_projectile_launcher_state(
     mut commands: Commands,
-    query: Query<(Entity, &crate::enemy::EnemyStats), Added<crate::enemy::EnemyStats>>,
+    query: Query<
+        (
+            Entity,
+            &crate::enemy::EnemyStats,
+            Option<&ProjectileLauncherState>,
+        ),
+        Changed<crate::enemy::EnemyStats>,
+    >,
 ) {
-    for (entity, enemy_stats) in query.iter() {
+    for (entity, enemy_stats, launcher) in query.iter() {
+        if launcher.is_some_and(|launcher| launcher.enemy_type == enemy_stats.enemy_type) {
+            continue;
+        }
         let crate::enemy::EnemyType::Ranged {
             projectile_damage,
             projectile_speed,
             projectile_lifetime,
             homing,
             delay,
+            pattern,
             ..
         } = enemy_stats.enemy_type
         else {
+            // the enemy may have been ranged before its stats were reloaded
+            commands.entity(entity).remove::<ProjectileLauncherState>();
             continue;
         };
 
@@ -316,6 +435,11 @@ fn add_projectile_launcher_state(
             projectile_lifetime,
             homing,
             timer: Timer::from_seconds(delay, TimerMode::Once),
+            pattern,
+            shots_left: 0,
+            burst_timer: Timer::default(),
+            aim: 0.0,
+            enemy_type: enemy_stats.enemy_type,
         };
         commands.entity(entity).insert(projectile_launcher_state);
     }
@@ -1,12 +1,16 @@
 use bevy::prelude::*;
-use bevy_math::vec2;
 
 #[derive(Debug, Default, Event, Clone)]
 pub struct ChangeRoom {
     /// Which room to go to
     pub next_room_name: String,
-    /// Where we're entering from, from the perspective of the new room
-    pub coming_from: Option<super::CardinalDirection>,
+    /// Which room we're coming from. The player will be placed next to the exit leading back there
+    pub coming_from: Option<String>,
+    /// Which exit of `coming_from` was taken, so the player is placed next to the exit it
+    /// links to, even if several lead back there
+    pub exit: Option<usize>,
+    /// Where to place the player in the new room. Takes priority over `coming_from`
+    pub arrival: Option<Vec2>,
 }
 
 pub fn handle_change_room(
@@ -15,12 +19,19 @@ pub fn handle_change_room(
     rooms: Res<super::Rooms>,
     enemy_stats: Res<Assets<crate::enemy::EnemyStats>>,
     boss_stats: Res<Assets<crate::enemy::BossStats>>,
+    difficulty_assets: Res<crate::difficulty::DifficultyAssets>,
+    difficulty_curves: Res<Assets<crate::difficulty::DifficultyCurve>>,
+    cycle_counter: Option<Res<crate::cycles::CycleCounter>>,
+    room_graph: Res<super::RoomGraph>,
     mut next_state: ResMut<NextState<crate::states::GameState>>,
+    mut errors: EventWriter<crate::error::GameError>,
 ) {
     // Only take the first event per frame, dropping the rest
     let Some(ChangeRoom {
         next_room_name,
         coming_from,
+        exit,
+        arrival,
     }) = reader.read().next()
     else {
         return;
@@ -28,25 +39,84 @@ pub fn handle_change_room(
 
     info!("Preparing to change rooms to {}", next_room_name);
 
+    // on failure nothing changes, so the player stays where they are
     let Some((info, assets)) = rooms.map.get(next_room_name) else {
-        error!("Room `{}` not loaded!", next_room_name);
-        panic!();
+        errors.send(crate::error::GameError::UnknownRoom(next_room_name.clone()));
+        return;
     };
 
-    let Some(melee_enemy_stats) = enemy_stats.get(&assets.melee_enemy_stats) else {
-        error!(
-            "No EnemyStats found with handle {:?}",
-            assets.melee_enemy_stats
-        );
-        panic!();
+    let Some(curve) = difficulty_curves.get(&difficulty_assets.curve) else {
+        errors.send(crate::error::GameError::MissingDifficultyCurve);
+        return;
     };
-    let Some(ranged_enemy_stats) = enemy_stats.get(&assets.ranged_enemy_stats) else {
-        error!(
-            "No EnemyStats found with handle {:?}",
-            assets.ranged_enemy_stats
-        );
-        panic!();
+    let cycle = cycle_counter.map(|c| c.count).unwrap_or(0);
+    match prepare_room(
+        info,
+        assets,
+        curve,
+        &room_graph,
+        cycle,
+        &enemy_stats,
+        &boss_stats,
+    ) {
+        Ok(current_room) => commands.insert_resource(current_room),
+        Err(e) => {
+            errors.send(e);
+            return;
+        }
+    }
+
+    let pos = match (arrival, coming_from) {
+        (Some(arrival), _) => *arrival,
+        (None, Some(previous_room)) => exit
+            .and_then(|exit| room_graph.link(previous_room, exit))
+            .and_then(|link| link.back)
+            .or_else(|| room_graph.exit_to(&info.name, previous_room))
+            .and_then(|index| info.exits.get(index))
+            .map(|exit| exit.arrival_point(info.rect))
+            .unwrap_or(info.rect.center()),
+        (None, None) => info.rect.center(),
     };
+    commands.insert_resource(crate::player::PlayerSpawnPosition { pos });
+
+    next_state.set(crate::states::GameState::RoomTransition);
+}
+
+/// Works out a room's level, and scales its enemies' stats to match
+pub fn prepare_room(
+    info: &super::RoomInfo,
+    assets: &super::RoomAssets,
+    curve: &crate::difficulty::DifficultyCurve,
+    room_graph: &super::RoomGraph,
+    cycle: u64,
+    enemy_stats: &Assets<crate::enemy::EnemyStats>,
+    boss_stats: &Assets<crate::enemy::BossStats>,
+) -> Result<super::CurrentRoom, crate::error::GameError> {
+    let distance = room_graph
+        .distance(super::START_ROOM, &info.name)
+        .unwrap_or(0);
+    let level = curve.room_level(info.fixed_level, distance, cycle);
+    let difficulty = curve.at(level);
+    info!("{} is level {}: {:?}", info.name, level, difficulty);
+
+    let mut roster = Vec::new();
+    for entry in info.roster.iter() {
+        let Some(stats) = enemy_stats.get(&entry.handles.stats) else {
+            return Err(crate::error::GameError::MissingEnemyStats {
+                room: info.name.clone(),
+                path: entry.stats.clone(),
+            });
+        };
+        roster.push(super::RosterEnemy {
+            stats: stats.scaled(&difficulty),
+            texture: entry.handles.texture.clone(),
+            projectile: entry
+                .handles
+                .projectile
+                .clone()
+                .unwrap_or(assets.projectile.clone()),
+        });
+    }
 
     let boss_stats = match &assets.boss_stats {
         None => {
@@ -55,44 +125,19 @@ pub fn handle_change_room(
         }
         Some(handle) => {
             let Some(boss_stats) = boss_stats.get(handle) else {
-                error!("No BossStats found with handle {:?}", handle);
-                panic!();
+                return Err(crate::error::GameError::MissingBossStats {
+                    room: info.name.clone(),
+                });
             };
-            Some(boss_stats.clone())
+            Some(boss_stats.scaled(&difficulty))
         }
     };
 
-    commands.insert_resource(crate::room::CurrentRoom {
+    Ok(super::CurrentRoom {
         info: info.clone(),
         assets: assets.clone(),
         boss_stats,
-        melee_enemy_stats: melee_enemy_stats.clone(),
-        ranged_enemy_stats: ranged_enemy_stats.clone(),
-    });
-
-    const DISTANCE_FROM_EXIT: f32 = 50.0;
-    let pos;
-    let room_rect = info.rect;
-    let half_size = room_rect.half_size();
-    match coming_from {
-        None => {
-            pos = room_rect.center();
-        }
-        Some(crate::room::CardinalDirection::North) => {
-            pos = room_rect.center() + vec2(0.0, half_size.y - DISTANCE_FROM_EXIT);
-        }
-        Some(crate::room::CardinalDirection::South) => {
-            pos = room_rect.center() - vec2(0.0, half_size.y - DISTANCE_FROM_EXIT);
-        }
-
-        Some(crate::room::CardinalDirection::East) => {
-            pos = room_rect.center() + vec2(half_size.x - DISTANCE_FROM_EXIT, 0.0);
-        }
-        Some(crate::room::CardinalDirection::West) => {
-            pos = room_rect.center() - vec2(half_size.x - DISTANCE_FROM_EXIT, 0.0);
-        }
-    }
-    commands.insert_resource(crate::player::PlayerSpawnPosition { pos });
-
-    next_state.set(crate::states::GameState::RoomTransition);
+        level,
+        roster,
+    })
 }
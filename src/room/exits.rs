@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use bevy_math::vec2;
+
+use super::CardinalDirection;
+
+/// How thick the walls around a room are
+pub const WALL_THICKNESS: f32 = 100.0;
+
+/// How far from an exit the player is placed when arriving through it
+const DISTANCE_FROM_EXIT: f32 = 50.0;
+
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct RoomExit {
+    /// Which room this exit leads to
+    pub target: String,
+    /// Where in this room the exit is
+    pub location: ExitLocation,
+    /// Where the player should appear in the target room.
+    /// If not given, the player appears next to the target room's exit leading back here
+    #[serde(default)]
+    pub arrival: Option<Vec2>,
+    /// Everything that has to be true before the exit can be used
+    #[serde(default)]
+    pub requires: Vec<super::ExitRequirement>,
+}
+
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub enum ExitLocation {
+    /// A section of one of the room's walls
+    Wall {
+        side: CardinalDirection,
+        /// Offset of the center of the exit from the center of the wall
+        #[serde(default)]
+        offset: f32,
+        /// Width of the exit along the wall. The exit covers the whole wall if this isn't given
+        #[serde(default)]
+        width: Option<f32>,
+    },
+    /// A door somewhere inside the room
+    Door { position: Vec2, size: Vec2 },
+}
+
+impl CardinalDirection {
+    /// The range covered by this side of the room, measured along the wall
+    /// (x for north and south, y for east and west)
+    pub fn span(self, room_rect: Rect) -> (f32, f32) {
+        match self {
+            CardinalDirection::North | CardinalDirection::South => {
+                (room_rect.min.x, room_rect.max.x)
+            }
+            CardinalDirection::East | CardinalDirection::West => (room_rect.min.y, room_rect.max.y),
+        }
+    }
+
+    /// Builds the rectangle covering the given range along this side of the room, extending
+    /// `WALL_THICKNESS` outward from the room's edge
+    pub fn wall_rect(self, room_rect: Rect, from: f32, to: f32) -> Rect {
+        match self {
+            CardinalDirection::North => {
+                Rect::new(from, room_rect.max.y, to, room_rect.max.y + WALL_THICKNESS)
+            }
+            CardinalDirection::South => {
+                Rect::new(from, room_rect.min.y - WALL_THICKNESS, to, room_rect.min.y)
+            }
+            CardinalDirection::East => {
+                Rect::new(room_rect.max.x, from, room_rect.max.x + WALL_THICKNESS, to)
+            }
+            CardinalDirection::West => {
+                Rect::new(room_rect.min.x - WALL_THICKNESS, from, room_rect.min.x, to)
+            }
+        }
+    }
+
+    /// Converts a position along this side and a distance into the room into a point
+    pub fn inside_point(self, room_rect: Rect, along: f32, inset: f32) -> Vec2 {
+        match self {
+            CardinalDirection::North => vec2(along, room_rect.max.y - inset),
+            CardinalDirection::South => vec2(along, room_rect.min.y + inset),
+            CardinalDirection::East => vec2(room_rect.max.x - inset, along),
+            CardinalDirection::West => vec2(room_rect.min.x + inset, along),
+        }
+    }
+}
+
+impl RoomExit {
+    /// The range this exit covers along its wall, or `None` if it isn't on a wall
+    pub fn wall_range(&self, room_rect: Rect) -> Option<(CardinalDirection, f32, f32)> {
+        let ExitLocation::Wall {
+            side,
+            offset,
+            width,
+        } = self.location
+        else {
+            return None;
+        };
+
+        let (lo, hi) = side.span(room_rect);
+        let center = (lo + hi) / 2.0 + offset;
+        let half_width = width.map(|w| w / 2.0).unwrap_or((hi - lo) / 2.0);
+        Some((
+            side,
+            f32::max(center - half_width, lo),
+            f32::min(center + half_width, hi),
+        ))
+    }
+
+    /// Which way the exit leads out of the room, as one step on a grid with y pointing north
+    pub fn grid_direction(&self, room_rect: Rect) -> IVec2 {
+        match self.location {
+            ExitLocation::Wall { side, .. } => match side {
+                CardinalDirection::North => IVec2::Y,
+                CardinalDirection::South => IVec2::NEG_Y,
+                CardinalDirection::East => IVec2::X,
+                CardinalDirection::West => IVec2::NEG_X,
+            },
+            ExitLocation::Door { position, .. } => {
+                // whichever wall the door is closest to facing
+                let offset = position - room_rect.center();
+                if offset.x.abs() > offset.y.abs() {
+                    IVec2::new(offset.x.signum() as i32, 0)
+                } else if offset.y > 0.0 {
+                    IVec2::Y
+                } else {
+                    IVec2::NEG_Y
+                }
+            }
+        }
+    }
+
+    /// Where a player arriving in this room through this exit should appear
+    pub fn arrival_point(&self, room_rect: Rect) -> Vec2 {
+        match self.location {
+            ExitLocation::Wall { .. } => {
+                let (side, from, to) = self
+                    .wall_range(room_rect)
+                    .expect("wall exits always have a range");
+                side.inside_point(room_rect, (from + to) / 2.0, DISTANCE_FROM_EXIT)
+            }
+            ExitLocation::Door { position, size } => {
+                // step away from the door, toward the middle of the room
+                let dir = (room_rect.center() - position).normalize_or(Vec2::NEG_Y);
+                let pos = position + dir * (size.length() / 2.0 + DISTANCE_FROM_EXIT);
+                let inner = room_rect.inflate(-DISTANCE_FROM_EXIT);
+                pos.clamp(inner.min, inner.max)
+            }
+        }
+    }
+}
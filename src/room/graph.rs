@@ -0,0 +1,269 @@
+use bevy::{prelude::*, utils::HashMap};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+
+/// How the rooms are connected to each other
+#[derive(Debug, Default, Clone, Copy, Resource, serde::Deserialize)]
+pub enum GraphMode {
+    /// Exits lead where the room files say they do
+    #[default]
+    Fixed,
+    /// Each cycle connects the rooms' exits up differently
+    Procedural,
+}
+
+/// Where an exit leads
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExitLink {
+    /// The room on the other side
+    pub room: String,
+    /// Where the player should appear in that room, overriding the exit leading back
+    pub arrival: Option<Vec2>,
+    /// The exit in that room that leads back through this link, if there is one
+    pub back: Option<usize>,
+}
+
+/// The connections between rooms for the current cycle
+#[derive(Debug, Default, Resource)]
+pub struct RoomGraph {
+    /// Where each room's exits lead, in the same order as `RoomInfo::exits`.
+    /// An exit without a link is walled off
+    pub links: HashMap<String, Vec<Option<ExitLink>>>,
+    /// The seed the graph was generated from, `None` for the fixed graph
+    pub seed: Option<u64>,
+}
+
+impl RoomGraph {
+    /// The graph as written in the rooms' files. Exits to rooms that weren't loaded are walled off
+    pub fn fixed(rooms: &super::Rooms) -> RoomGraph {
+        let links = rooms
+            .map
+            .iter()
+            .map(|(name, (info, _))| {
+                let exits = info
+                    .exits
+                    .iter()
+                    .enumerate()
+                    .map(|(i, exit)| {
+                        let Some((target, _)) = rooms.map.get(&exit.target) else {
+                            warn!(
+                                "RoomGraph::fixed: {name} has an exit to {}, which isn't loaded",
+                                exit.target
+                            );
+                            return None;
+                        };
+                        // the second exit to a room pairs up with the second exit back, and so on
+                        let nth = info.exits[..i]
+                            .iter()
+                            .filter(|other| other.target == exit.target)
+                            .count();
+                        let backs: Vec<usize> = (0..target.exits.len())
+                            .filter(|j| target.exits[*j].target == *name)
+                            .collect();
+                        Some(ExitLink {
+                            room: exit.target.clone(),
+                            arrival: exit.arrival,
+                            back: backs.get(nth).or(backs.first()).copied(),
+                        })
+                    })
+                    .collect();
+                (name.clone(), exits)
+            })
+            .collect();
+        RoomGraph { links, seed: None }
+    }
+
+    /// Connects the rooms' exits up randomly, so that every room can be reached from
+    /// `start`. Links always go both ways, and no two rooms are linked twice. Exits with
+    /// requirements keep leading where the room files say, so what they guard stays behind them.
+    ///
+    /// Returns `None` if the rooms don't have enough exits between them to all be connected
+    pub fn generate(rooms: &super::Rooms, start: &str, seed: u64) -> Option<RoomGraph> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+
+        // every exit of every room, as (room, exit index)
+        let mut names: Vec<&String> = rooms.map.keys().collect();
+        names.sort();
+        let num_exits = |name: &str| rooms.map[name].0.exits.len();
+        if let Some(name) = names
+            .iter()
+            .find(|name| name.as_str() != start && num_exits(name) == 0)
+        {
+            warn!("RoomGraph::generate: {name} has no exits, so it can't be connected");
+            return None;
+        }
+
+        let mut links: HashMap<String, Vec<Option<ExitLink>>> = names
+            .iter()
+            .map(|name| ((*name).clone(), vec![None; num_exits(name)]))
+            .collect();
+        let connect = |links: &mut HashMap<String, Vec<Option<ExitLink>>>,
+                       (a, i): (&str, usize),
+                       (b, j): (&str, usize)| {
+            links.get_mut(a).unwrap()[i] = Some(ExitLink {
+                room: b.to_string(),
+                arrival: None,
+                back: Some(j),
+            });
+            links.get_mut(b).unwrap()[j] = Some(ExitLink {
+                room: a.to_string(),
+                arrival: None,
+                back: Some(i),
+            });
+        };
+
+        // link gated exits up first, along with the exit leading back through them if there's
+        // one. A room whose exits are all taken up by this hangs off the room on the other side
+        for name in names.iter().copied() {
+            for (i, exit) in rooms.map[name].0.exits.iter().enumerate() {
+                if exit.requires.is_empty()
+                    || links[name][i].is_some()
+                    || !rooms.map.contains_key(&exit.target)
+                {
+                    continue;
+                }
+                let target_exits = &rooms.map[&exit.target].0.exits;
+                let back = (0..target_exits.len()).find(|j| {
+                    target_exits[*j].target == *name && links[&exit.target][*j].is_none()
+                });
+                links.get_mut(name).unwrap()[i] = Some(ExitLink {
+                    room: exit.target.clone(),
+                    arrival: exit.arrival,
+                    back,
+                });
+                if let Some(j) = back {
+                    links.get_mut(&exit.target).unwrap()[j] = Some(ExitLink {
+                        room: name.clone(),
+                        arrival: target_exits[j].arrival,
+                        back: Some(i),
+                    });
+                }
+            }
+        }
+        let free_exits = |links: &HashMap<String, Vec<Option<ExitLink>>>, name: &str| {
+            (0..num_exits(name))
+                .filter(|i| links[name][*i].is_none())
+                .collect::<Vec<_>>()
+        };
+
+        // build a spanning tree out from the start, adding rooms with more exits first
+        // so there are always open exits to hang the next room off
+        let mut open: Vec<(&str, usize)> = free_exits(&links, start)
+            .into_iter()
+            .map(|i| (start, i))
+            .collect();
+        let mut remaining: Vec<&str> = names
+            .iter()
+            .map(|name| name.as_str())
+            .filter(|name| *name != start && !free_exits(&links, name).is_empty())
+            .collect();
+        remaining.shuffle(&mut rng);
+        remaining.sort_by_key(|name| std::cmp::Reverse(free_exits(&links, name).len()));
+
+        for room in remaining {
+            if open.is_empty() {
+                warn!("RoomGraph::generate: ran out of exits before {room} could be connected");
+                return None;
+            }
+            let from = open.swap_remove(rng.gen_range(0..open.len()));
+            let mut room_exits: Vec<(&str, usize)> = free_exits(&links, room)
+                .into_iter()
+                .map(|i| (room, i))
+                .collect();
+            let to = room_exits.swap_remove(rng.gen_range(0..room_exits.len()));
+            connect(&mut links, from, to);
+            open.extend(room_exits);
+        }
+
+        // link up whatever exits are left, anything that can't be linked gets walled off
+        open.shuffle(&mut rng);
+        while let Some(from) = open.pop() {
+            let already_linked = |other: &str| {
+                links[from.0]
+                    .iter()
+                    .flatten()
+                    .any(|link| link.room == other)
+            };
+            if let Some(index) = open
+                .iter()
+                .position(|to| to.0 != from.0 && !already_linked(to.0))
+            {
+                let to = open.swap_remove(index);
+                connect(&mut links, from, to);
+            }
+        }
+
+        let graph = RoomGraph {
+            links,
+            seed: Some(seed),
+        };
+        // a gated exit can leave a room hanging off one that never got connected
+        if let Some((name, _)) = rooms
+            .map
+            .iter()
+            .find(|(name, (info, _))| info.boss && graph.distance(start, name).is_none())
+        {
+            warn!("RoomGraph::generate: the boss room {name} can't be reached from {start}");
+            return None;
+        }
+        Some(graph)
+    }
+
+    /// Where the given exit of a room leads, if anywhere
+    pub fn link(&self, room: &str, exit: usize) -> Option<&ExitLink> {
+        self.links.get(room)?.get(exit)?.as_ref()
+    }
+
+    /// The index of the exit in `room` that leads to `target`
+    pub fn exit_to(&self, room: &str, target: &str) -> Option<usize> {
+        self.links
+            .get(room)?
+            .iter()
+            .position(|link| link.as_ref().is_some_and(|link| link.room == target))
+    }
+
+    /// How many exits have to be taken to get from one room to another,
+    /// or `None` if there's no way there
+    pub fn distance(&self, from: &str, to: &str) -> Option<usize> {
+        let mut visited = bevy::utils::HashSet::from([from]);
+        let mut queue = std::collections::VecDeque::from([(from, 0)]);
+        while let Some((name, distance)) = queue.pop_front() {
+            if name == to {
+                return Some(distance);
+            }
+            let Some(exits) = self.links.get(name) else {
+                continue;
+            };
+            for link in exits.iter().flatten() {
+                if visited.insert(link.room.as_str()) {
+                    queue.push_back((link.room.as_str(), distance + 1));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Sets up the room graph for a new cycle
+pub fn build_room_graph(
+    mut commands: Commands,
+    rooms: Res<super::Rooms>,
+    mode: Res<GraphMode>,
+    mut rng: ResMut<crate::rand::GlobalRng>,
+    resume: Option<Res<crate::save_data::ResumeCycle>>,
+) {
+    let graph = match *mode {
+        GraphMode::Fixed => RoomGraph::fixed(&rooms),
+        GraphMode::Procedural => {
+            // a resumed cycle has to be laid out the same way it was when it was saved
+            let seed = resume
+                .and_then(|resume| resume.0.graph_seed)
+                .unwrap_or_else(|| rng.gen());
+            info!("build_room_graph: generating room graph with seed {seed}");
+            RoomGraph::generate(&rooms, super::START_ROOM, seed).unwrap_or_else(|| {
+                warn!("build_room_graph: couldn't generate a room graph, using the fixed one");
+                RoomGraph::fixed(&rooms)
+            })
+        }
+    };
+    commands.insert_resource(graph);
+}
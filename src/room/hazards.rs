@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+
+/// An area of a room that affects anything standing in it
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct Hazard {
+    /// The area the hazard covers
+    pub rect: Rect,
+    pub kind: HazardKind,
+}
+
+#[derive(Debug, Clone, Copy, Reflect, serde::Deserialize)]
+pub enum HazardKind {
+    /// Deals `damage` to everything standing in it every `interval` seconds
+    Lava { damage: f32, interval: f32 },
+    /// Scales acceleration, so values below 1 make movement slide
+    Ice { acceleration: f32 },
+    /// Heals `heal` health every `interval` seconds, and scales movement speed by `speed`
+    Consecrated {
+        heal: f32,
+        interval: f32,
+        speed: f32,
+    },
+}
+
+impl HazardKind {
+    /// How often the hazard's periodic effect happens, if it has one
+    fn interval(&self) -> Option<f32> {
+        match self {
+            HazardKind::Lava { interval, .. } | HazardKind::Consecrated { interval, .. } => {
+                Some(*interval)
+            }
+            HazardKind::Ice { .. } => None,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            HazardKind::Lava { .. } => Color::srgba(1.0, 0.3, 0.0, 0.5),
+            HazardKind::Ice { .. } => Color::srgba(0.7, 0.9, 1.0, 0.4),
+            HazardKind::Consecrated { .. } => Color::srgba(1.0, 0.9, 0.4, 0.3),
+        }
+    }
+}
+
+/// A hazard that's been spawned into the current room
+#[derive(Debug, Component)]
+pub struct HazardZone {
+    hazard: Hazard,
+    /// Counts down to the next periodic effect
+    timer: Timer,
+}
+
+pub fn spawn_hazards(mut commands: Commands, current_room: Res<super::CurrentRoom>) {
+    for hazard in current_room.info.hazards.iter() {
+        let interval = hazard.kind.interval().unwrap_or(1.0);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: hazard.kind.color(),
+                    custom_size: Some(hazard.rect.size()),
+                    ..Default::default()
+                },
+                // just above the floor
+                transform: Transform::from_translation(hazard.rect.center().extend(-9.0)),
+                ..Default::default()
+            },
+            HazardZone {
+                hazard: hazard.clone(),
+                timer: Timer::from_seconds(interval, TimerMode::Repeating),
+            },
+            super::RoomObject,
+            Name::new("Hazard"),
+        ));
+    }
+}
+
+/// Everything hazards can affect, along with what they need to affect it
+type HazardTargets<'a> = (
+    Entity,
+    &'a GlobalTransform,
+    &'a mut crate::character_controller::CharacterController,
+    Option<&'a mut crate::enemy::EnemyHealth>,
+    Has<crate::player::Player>,
+);
+
+pub fn apply_hazards(
+    mut zones: Query<&mut HazardZone>,
+    mut characters: Query<HazardTargets>,
+    mut player_health: ResMut<crate::player::PlayerHealth>,
+    mut damage_events: EventWriter<crate::damage::DamageEvent>,
+    time: Res<Time>,
+) {
+    for mut zone in zones.iter_mut() {
+        zone.timer.tick(time.delta());
+    }
+
+    for (entity, global_transform, mut controller, mut enemy_health, is_player) in
+        characters.iter_mut()
+    {
+        let pos = global_transform.translation().truncate();
+
+        let mut acceleration_scale = 1.0;
+        let mut speed_scale = 1.0;
+        for zone in zones.iter().filter(|zone| zone.hazard.rect.contains(pos)) {
+            let times = zone.timer.times_finished_this_tick();
+            match zone.hazard.kind {
+                HazardKind::Lava { damage, .. } => {
+                    for _ in 0..times {
+                        damage_events.send(if is_player {
+                            crate::damage::DamageEvent::Player { damage }
+                        } else {
+                            crate::damage::DamageEvent::Enemy { entity, damage }
+                        });
+                    }
+                }
+                HazardKind::Ice { acceleration } => {
+                    acceleration_scale *= acceleration;
+                }
+                HazardKind::Consecrated { heal, speed, .. } => {
+                    speed_scale *= speed;
+                    if times == 0 {
+                        continue;
+                    }
+                    let heal = heal * times as f32;
+                    if is_player && !player_health.dead {
+                        player_health.current =
+                            f32::min(player_health.current + heal, player_health.maximum);
+                    } else if let Some(enemy_health) = enemy_health.as_mut() {
+                        enemy_health.current =
+                            f32::min(enemy_health.current + heal, enemy_health.maximum);
+                    }
+                }
+            }
+        }
+
+        // only touch the controller when something changed, so change detection stays useful
+        if controller.acceleration_scale != acceleration_scale {
+            controller.acceleration_scale = acceleration_scale;
+        }
+        if controller.speed_scale != speed_scale {
+            controller.speed_scale = speed_scale;
+        }
+    }
+}
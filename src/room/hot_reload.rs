@@ -0,0 +1,267 @@
+use bevy::prelude::*;
+
+/// Picks up edits to room, enemy and boss files while the game runs.
+/// Files are only watched when the `file_watcher` feature is enabled
+#[derive(Debug, Default)]
+pub struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HotReloadSettings>()
+            .add_event::<RoomDataChanged>()
+            .add_systems(
+                Update,
+                (reload_room_info, reload_enemy_stats)
+                    .run_if(not(in_state(crate::states::AppState::CoreLoading))),
+            )
+            .add_systems(
+                Update,
+                refresh_current_room.run_if(in_state(crate::states::GameState::InGame)),
+            );
+    }
+}
+
+/// What happens to the current room when its files change
+#[derive(Debug, Default, Clone, Copy, Resource, serde::Deserialize)]
+pub struct HotReloadSettings {
+    /// Despawn the room and spawn it again with a fresh layout when its `*.info.ron` changes.
+    /// Otherwise only the enemies already in the room are updated
+    #[serde(default)]
+    pub rebuild_room: bool,
+}
+
+/// The data the current room was prepared from has changed
+#[derive(Debug, Default, Event)]
+struct RoomDataChanged;
+
+fn reload_room_info(
+    mut events: EventReader<AssetEvent<super::RoomInfo>>,
+    mut pending: Local<Vec<AssetId<super::RoomInfo>>>,
+    asset_server: Res<AssetServer>,
+    room_infos: Res<Assets<super::RoomInfo>>,
+    mut rooms: ResMut<super::Rooms>,
+    mut room_graph: ResMut<super::RoomGraph>,
+    current_room: Option<Res<super::CurrentRoom>>,
+    settings: Res<HotReloadSettings>,
+    mut room_state: ResMut<super::PersistentRoomState>,
+    player_query: Query<&GlobalTransform, With<crate::player::Player>>,
+    mut change_room: EventWriter<super::ChangeRoom>,
+    mut changed: EventWriter<RoomDataChanged>,
+) {
+    for event in events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if !pending.contains(id) {
+                pending.push(*id);
+            }
+        }
+    }
+
+    // a reloaded room can refer to enemies that haven't been loaded yet
+    let mut reloaded = false;
+    pending.retain(|id| {
+        match asset_server.recursive_dependency_load_state(*id) {
+            bevy::asset::RecursiveDependencyLoadState::Loaded => {}
+            bevy::asset::RecursiveDependencyLoadState::Failed => {
+                error!("reload_room_info: failed to load the roster of a reloaded room");
+                return false;
+            }
+            _ => return true,
+        }
+
+        let Some(info) = room_infos.get(*id) else {
+            return false;
+        };
+        let Some((name, (old_info, _))) = rooms
+            .map
+            .iter_mut()
+            .find(|(_, (_, assets))| assets.info.id() == *id)
+        else {
+            return false;
+        };
+        if info.name != *name {
+            warn!(
+                "reload_room_info: {name} was renamed to {}, restart the game to pick that up",
+                info.name
+            );
+            return false;
+        }
+
+        info!("reload_room_info: reloaded {name}");
+        *old_info = info.clone();
+        reloaded = true;
+
+        let Some(current_room) = current_room.as_ref() else {
+            return false;
+        };
+        if current_room.info.name != *name {
+            return false;
+        }
+        if settings.rebuild_room {
+            // throw away the old layout, it may not fit the room anymore
+            room_state.rooms.remove(name);
+            change_room.send(super::ChangeRoom {
+                next_room_name: name.clone(),
+                arrival: player_query
+                    .get_single()
+                    .ok()
+                    .map(|transform| transform.translation().truncate()),
+                ..Default::default()
+            });
+        } else {
+            changed.send(RoomDataChanged);
+        }
+        false
+    });
+
+    // exits may have been added, removed or pointed somewhere else. A generated graph is
+    // generated again from the same seed, so it only changes if the exits did
+    if reloaded {
+        *room_graph = room_graph
+            .seed
+            .and_then(|seed| super::RoomGraph::generate(&rooms, super::START_ROOM, seed))
+            .unwrap_or_else(|| super::RoomGraph::fixed(&rooms));
+    }
+}
+
+fn reload_enemy_stats(
+    mut enemy_events: EventReader<AssetEvent<crate::enemy::EnemyStats>>,
+    mut boss_events: EventReader<AssetEvent<crate::enemy::BossStats>>,
+    current_room: Option<Res<super::CurrentRoom>>,
+    mut changed: EventWriter<RoomDataChanged>,
+) {
+    let modified_enemies: Vec<_> = enemy_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let modified_bosses: Vec<_> = boss_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    let Some(current_room) = current_room else {
+        return;
+    };
+    let roster_changed = current_room
+        .info
+        .roster
+        .iter()
+        .any(|entry| modified_enemies.contains(&entry.handles.stats.id()));
+    let boss_changed = current_room
+        .assets
+        .boss_stats
+        .as_ref()
+        .is_some_and(|handle| modified_bosses.contains(&handle.id()));
+    if roster_changed || boss_changed {
+        info!(
+            "reload_enemy_stats: enemy stats for {} changed",
+            current_room.info.name
+        );
+        changed.send(RoomDataChanged);
+    }
+}
+
+/// Prepares the current room again from the reloaded data, and updates the enemies in it
+fn refresh_current_room(
+    mut events: EventReader<RoomDataChanged>,
+    mut current_room: ResMut<super::CurrentRoom>,
+    rooms: Res<super::Rooms>,
+    room_state: Res<super::PersistentRoomState>,
+    room_graph: Res<super::RoomGraph>,
+    cycle_counter: Option<Res<crate::cycles::CycleCounter>>,
+    difficulty_assets: Res<crate::difficulty::DifficultyAssets>,
+    difficulty_curves: Res<Assets<crate::difficulty::DifficultyCurve>>,
+    enemy_stats: Res<Assets<crate::enemy::EnemyStats>>,
+    boss_stats: Res<Assets<crate::enemy::BossStats>>,
+    mut errors: EventWriter<crate::error::GameError>,
+    // only enemies have a `SpawnerIndex`
+    mut enemy_query: Query<(
+        &super::SpawnerIndex,
+        &mut crate::enemy::EnemyStats,
+        &mut crate::enemy::EnemyHealth,
+        &mut crate::character_controller::CharacterController,
+        Option<&mut crate::enemy::BossStats>,
+    )>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    let Some((info, assets)) = rooms.map.get(&current_room.info.name) else {
+        return;
+    };
+    let Some(curve) = difficulty_curves.get(&difficulty_assets.curve) else {
+        errors.send(crate::error::GameError::MissingDifficultyCurve);
+        return;
+    };
+    let cycle = cycle_counter.map(|c| c.count).unwrap_or(0);
+    // keep the room as it was if the new data doesn't work out
+    *current_room = match super::events::prepare_room(
+        info,
+        assets,
+        curve,
+        &room_graph,
+        cycle,
+        &enemy_stats,
+        &boss_stats,
+    ) {
+        Ok(current_room) => current_room,
+        Err(e) => {
+            errors.send(e);
+            return;
+        }
+    };
+
+    let Some(this_room_state) = room_state.rooms.get(&current_room.info.name) else {
+        return;
+    };
+    let mut count = 0;
+    for (spawner_index, mut stats, mut health, mut controller, boss) in enemy_query.iter_mut() {
+        let Some(spawner) = this_room_state.spawners.get(spawner_index.0) else {
+            continue;
+        };
+        let mut new_stats = match spawner.ty {
+            super::SpawnerType::Roster(index) => {
+                let Some(enemy) = current_room.roster.get(index) else {
+                    warn!(
+                        "refresh_current_room: {} has no roster entry with index {index} anymore",
+                        current_room.info.name
+                    );
+                    continue;
+                };
+                enemy.stats.clone()
+            }
+            super::SpawnerType::Boss => {
+                let Some(boss_stats) = current_room.boss_stats.as_ref() else {
+                    continue;
+                };
+                if let Some(mut boss) = boss {
+                    *boss = boss_stats.clone();
+                }
+                // keep the phase the boss had reached
+                boss_stats.phase_stats(boss_stats.phase_at(health.current / health.maximum))
+            }
+        };
+
+        new_stats
+            .behavior
+            .get_or_insert_with(|| crate::behavior::Behavior::default_for(new_stats.search_time));
+
+        // keep the same fraction of health
+        let fraction = health.current / health.maximum;
+        health.maximum = new_stats.health;
+        health.current = new_stats.health * fraction;
+        controller.max_speed = new_stats.speed;
+        *stats = new_stats;
+        count += 1;
+    }
+    info!(
+        "refresh_current_room: updated {count} enemies in {}",
+        current_room.info.name
+    );
+}
@@ -1,11 +1,34 @@
 use bevy::{prelude::*, utils::HashMap};
-use bevy_asset_loader::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 mod events;
+mod exits;
+mod graph;
+mod hazards;
+mod hot_reload;
+mod navigation;
+mod obstacles;
+mod placement;
+mod requirements;
+mod respawn;
+mod rewards;
+mod roster;
 mod spawn;
 
 pub use events::ChangeRoom;
+pub use exits::{ExitLocation, RoomExit};
+pub use graph::{build_room_graph, GraphMode, RoomGraph};
+pub use hazards::{Hazard, HazardKind};
+pub use hot_reload::HotReloadSettings;
+pub use navigation::{NavGrid, NavPath};
+pub use obstacles::{ObstacleEntry, ObstacleKind};
+pub use requirements::{ExitBarrier, ExitLocks, ExitRequirement, StoryFlags};
+pub use respawn::RespawnPolicy;
+pub use rewards::{RoomClearedEvent, RoomReward};
+pub use roster::{RosterEnemy, RosterEntry};
+
+/// The room every cycle starts in
+pub const START_ROOM: &str = "Lovely Cottage";
 
 #[derive(Debug, Default)]
 pub struct RoomPlugin;
@@ -13,7 +36,23 @@ pub struct RoomPlugin;
 impl Plugin for RoomPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<events::ChangeRoom>()
+            .add_event::<RoomClearedEvent>()
             .init_resource::<PersistentRoomState>()
+            .init_resource::<GraphMode>()
+            .init_resource::<RoomGraph>()
+            .init_resource::<StoryFlags>()
+            .init_resource::<ExitLocks>()
+            .init_resource::<NavGrid>()
+            .add_plugins(hot_reload::HotReloadPlugin)
+            .add_systems(
+                Update,
+                (
+                    spawn::record_enemy_snapshots,
+                    obstacles::record_obstacle_positions,
+                )
+                    .before(events::handle_change_room)
+                    .run_if(in_state(crate::states::GameState::InGame)),
+            )
             .add_systems(
                 Update,
                 events::handle_change_room.run_if(
@@ -24,7 +63,24 @@ impl Plugin for RoomPlugin {
             )
             .add_systems(
                 Update,
-                detect_wall_collisions.run_if(in_state(crate::states::GameState::InGame)),
+                (
+                    detect_wall_collisions,
+                    hazards::apply_hazards,
+                    (
+                        requirements::update_exit_locks,
+                        requirements::update_exit_barriers,
+                    )
+                        .chain(),
+                    (rewards::check_room_cleared, rewards::grant_room_rewards).chain(),
+                    rewards::open_chests,
+                    obstacles::damage_obstacles,
+                    obstacles::push_obstacles,
+                    (navigation::update_nav_grid, navigation::plan_enemy_paths).chain(),
+                    respawn::tick_time_away,
+                    respawn::trickle_respawn,
+                    spawn::summon_adds,
+                )
+                    .run_if(in_state(crate::states::GameState::InGame)),
             )
             .add_systems(
                 OnEnter(crate::states::GameState::RoomTransition),
@@ -33,6 +89,9 @@ impl Plugin for RoomPlugin {
                     crate::player::destroy_player,
                     crate::camera::destroy_game_camera,
                     spawn::spawn_room,
+                    navigation::build_nav_grid,
+                    hazards::spawn_hazards,
+                    rewards::spawn_unclaimed_chest,
                     apply_deferred,
                     crate::player::spawn_player,
                     crate::camera::spawn_game_camera,
@@ -55,8 +114,10 @@ impl Plugin for RoomPlugin {
 fn detect_wall_collisions(
     mut collisions: EventReader<CollisionEvent>,
     player_query: Query<Entity, With<crate::player::Player>>,
-    wall_query: Query<&Wall>,
+    exit_query: Query<&Exit>,
     current_room: Res<CurrentRoom>,
+    room_graph: Res<RoomGraph>,
+    exit_locks: Res<ExitLocks>,
     mut writer: EventWriter<events::ChangeRoom>,
 ) {
     for ev in collisions.read() {
@@ -69,30 +130,34 @@ fn detect_wall_collisions(
             continue;
         };
 
-        let Ok(wall) = wall_query.get(*e1).or(wall_query.get(*e2)) else {
+        let Ok(exit) = exit_query.get(*e1).or(exit_query.get(*e2)) else {
+            // We collided with a wall that doesn't link to another room
             continue;
         };
 
-        info!("Collided with wall");
-
-        let current_room_info = &current_room.info;
-
-        // have to wrap this in parentheses because rust complains about the `{` before the `else`
-        let Some(next_room_name) = (match wall.0 {
-            CardinalDirection::North => &current_room_info.north,
-            CardinalDirection::South => &current_room_info.south,
-            CardinalDirection::East => &current_room_info.east,
-            CardinalDirection::West => &current_room_info.west,
-        }) else {
-            // We collided with a wall that doesn't link to another room
+        let Some(link) = room_graph.link(&current_room.info.name, exit.0) else {
+            error!(
+                "detect_wall_collisions: exit {} of {} doesn't lead anywhere",
+                exit.0, current_room.info.name
+            );
             continue;
         };
 
-        info!("Wall had a link, trying to go to {:?}", next_room_name);
+        if let Some(reason) = exit_locks.reason(exit.0) {
+            info!(
+                "Collided with exit to {:?}, but it's locked: {reason}",
+                link.room
+            );
+            continue;
+        }
+
+        info!("Collided with exit, trying to go to {:?}", link.room);
 
         writer.send(events::ChangeRoom {
-            next_room_name: next_room_name.clone(),
-            coming_from: Some(wall.0.opposite()),
+            next_room_name: link.room.clone(),
+            coming_from: Some(current_room.info.name.clone()),
+            exit: Some(exit.0),
+            arrival: link.arrival,
         });
         // return after sending one of these so we don't try to go to multiple rooms at once
         return;
@@ -103,8 +168,10 @@ fn detect_wall_collisions(
 pub struct CurrentRoom {
     pub info: RoomInfo,
     pub boss_stats: Option<crate::enemy::BossStats>,
-    pub melee_enemy_stats: crate::enemy::EnemyStats,
-    pub ranged_enemy_stats: crate::enemy::EnemyStats,
+    /// How difficult this room is. Enemy stats have already been scaled to match
+    pub level: u64,
+    /// The enemies that can appear in this room, in the same order as `info.roster`
+    pub roster: Vec<RosterEnemy>,
     pub assets: RoomAssets,
 }
 
@@ -113,26 +180,19 @@ pub struct Rooms {
     pub map: HashMap<String, (RoomInfo, RoomAssets)>,
 }
 
-#[derive(Debug, Clone, AssetCollection, Asset, Reflect, Resource)]
+/// Everything a room's `*.assets.ron` file refers to, keyed the same way
+#[derive(Debug, Clone, Reflect)]
 pub struct RoomAssets {
-    #[asset(key = "background_texture")]
+    /// The room's `*.info.ron` file. Kept loaded so edits to it can be picked up
+    pub info: Handle<RoomInfo>,
     pub background_texture: Handle<Image>,
-    #[asset(key = "obstacle_texture")]
     pub obstacle_texture: Handle<Image>,
 
-    #[asset(key = "boss_stats", optional)]
     pub boss_stats: Option<Handle<crate::enemy::BossStats>>,
+    /// The boss's sprite. If not given, the boss borrows the sprite of a roster enemy
+    pub boss_texture: Option<Handle<Image>>,
 
-    #[asset(key = "melee_enemy_texture")]
-    pub melee_enemy_texture: Handle<Image>,
-    #[asset(key = "melee_enemy_stats")]
-    pub melee_enemy_stats: Handle<crate::enemy::EnemyStats>,
-
-    #[asset(key = "ranged_enemy_texture")]
-    pub ranged_enemy_texture: Handle<Image>,
-    #[asset(key = "ranged_enemy_stats")]
-    pub ranged_enemy_stats: Handle<crate::enemy::EnemyStats>,
-    #[asset(key = "projectile")]
+    /// Sprite for projectiles from enemies that don't have their own
     pub projectile: Handle<Image>,
 }
 
@@ -141,17 +201,71 @@ pub struct RoomInfo {
     pub name: String,
     pub rect: Rect,
     pub boss: bool,
-    pub num_melee_enemies: usize,
-    pub num_ranged_enemies: usize,
+    /// How many enemies to spawn, not counting the boss
+    pub num_enemies: usize,
     pub num_obstacles: usize,
+    /// The kinds of obstacles that can appear in this room. Without any, they're all static
+    #[serde(default)]
+    pub obstacle_kinds: Vec<ObstacleEntry>,
+
+    /// The kinds of enemies that can appear in this room
+    #[serde(default)]
+    pub roster: Vec<RosterEntry>,
+
+    /// Areas of the room that affect anything standing in them
+    #[serde(default)]
+    pub hazards: Vec<Hazard>,
+
+    /// Exits leading to other rooms
+    #[serde(default)]
+    pub exits: Vec<RoomExit>,
+
+    /// What the player gets for defeating everything in the room
+    #[serde(default)]
+    pub reward: Option<RoomReward>,
+
+    /// When defeated enemies come back during a cycle
+    #[serde(default)]
+    pub respawn: RespawnPolicy,
+
+    /// Spawners and obstacles placed by hand. They count toward the totals above,
+    /// and random placement fills in whatever is left
+    #[serde(default)]
+    pub layout: Option<RoomLayout>,
+
+    /// Minimum distance between randomly placed spawners and obstacles
+    #[serde(default = "default_spawn_spacing")]
+    pub spawn_spacing: f32,
+    /// Nothing is placed randomly within this distance of where the player enters the room
+    #[serde(default = "default_safe_radius")]
+    pub safe_radius: f32,
+
+    /// Always use this level for the room, instead of working it out from where the room is
+    pub fixed_level: Option<u64>,
+}
 
-    // links
-    pub north: Option<String>,
-    pub south: Option<String>,
-    pub east: Option<String>,
-    pub west: Option<String>,
+fn default_spawn_spacing() -> f32 {
+    48.0
+}
 
-    pub fixed_level: Option<u64>,
+fn default_safe_radius() -> f32 {
+    200.0
+}
+
+#[derive(Debug, Default, Clone, Reflect, serde::Deserialize)]
+pub struct RoomLayout {
+    /// Positions of hand-placed obstacles
+    #[serde(default)]
+    pub obstacles: Vec<Vec2>,
+    /// Hand-placed spawners
+    #[serde(default)]
+    pub spawners: Vec<LayoutSpawner>,
+}
+
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct LayoutSpawner {
+    pub position: Vec2,
+    pub ty: SpawnerType,
 }
 
 #[derive(Debug, Default, Component)]
@@ -161,9 +275,9 @@ pub struct RoomObject;
 struct Floor;
 
 #[derive(Debug, Default, Component)]
-struct Obstacle;
+pub struct Obstacle;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, serde::Deserialize)]
 pub enum CardinalDirection {
     North,
     South,
@@ -171,21 +285,15 @@ pub enum CardinalDirection {
     West,
 }
 
-impl CardinalDirection {
-    pub fn opposite(self) -> Self {
-        match self {
-            CardinalDirection::North => CardinalDirection::South,
-            CardinalDirection::East => CardinalDirection::West,
-            CardinalDirection::South => CardinalDirection::North,
-            CardinalDirection::West => CardinalDirection::East,
-        }
-    }
-}
+#[derive(Debug, Default, Component)]
+struct Wall;
 
+/// Marks a section of wall or a door leading to another room.
+/// Holds the index of the exit in the current room's `RoomInfo::exits`
 #[derive(Debug, Component)]
-struct Wall(CardinalDirection);
+pub struct Exit(pub usize);
 
-#[derive(Debug, Component, Default)]
+#[derive(Debug, Component)]
 struct Spawner {
     ty: SpawnerType,
     index: usize,
@@ -196,27 +304,43 @@ struct Spawner {
 pub struct PersistentRoomState {
     pub rooms: HashMap<String, RoomState>,
 }
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
 pub struct RoomState {
     pub obstacles: Vec<ObstacleState>,
     pub spawners: Vec<SpawnerState>,
+    /// Every spawner has been defeated, see `RoomClearedEvent`. Unset again when defeated
+    /// enemies respawn
+    #[serde(default)]
+    pub cleared: bool,
+    /// The room's reward has been given out. Stays set when enemies respawn, so the reward
+    /// is only given out once per cycle
+    #[serde(default)]
+    pub reward_claimed: bool,
+    /// Seconds spent in other rooms since the player last left this one, see `RespawnPolicy`
+    #[serde(default)]
+    pub time_away: f32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct ObstacleState {
-    position: Vec2,
+    /// Where the obstacle is. Pushable obstacles update this when the player leaves the room
+    pub position: Vec2,
+    #[serde(default)]
+    pub kind: ObstacleKind,
+    /// Destroyed obstacles stay gone for the rest of the cycle
+    #[serde(default)]
+    pub destroyed: bool,
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, serde::Deserialize, serde::Serialize)]
 pub enum SpawnerType {
-    #[default]
-    Melee,
-    Ranged,
+    /// Spawns the enemy at this index in the room's roster
+    Roster(usize),
     Boss,
 }
 
 /// Stores information about a spawner so it will behave consistently during a cycle
-#[derive(Debug)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct SpawnerState {
     /// Where the spawner was placed
     pub position: Vec2,
@@ -225,12 +349,25 @@ pub struct SpawnerState {
     pub active: bool,
     /// Which type of enemy this spawner spawns
     pub ty: SpawnerType,
+    /// How the spawner's enemy was doing when the player last left the room.
+    /// `None` if the player hasn't left since it spawned
+    #[serde(default)]
+    pub snapshot: Option<EnemySnapshot>,
+}
+
+/// A surviving enemy, as it was when the player left its room
+#[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub struct EnemySnapshot {
+    pub health: f32,
+    pub position: Vec2,
+    #[serde(default)]
+    pub state: crate::enemy::EnemyState,
 }
 
 #[derive(Debug, Component)]
 pub struct SpawnerIndex(pub usize);
 
-#[derive(Bundle, Default)]
+#[derive(Bundle)]
 struct SpawnerBundle {
     transform: Transform,
     global_transform: GlobalTransform,
@@ -0,0 +1,383 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+/// Width and height of a navigation grid cell
+const CELL_SIZE: f32 = 32.0;
+/// How far an enemy's center has to stay from an obstacle's collider to get past it
+const CLEARANCE: f32 = 16.0;
+/// How often a chasing enemy works out a new path
+const REPLAN_INTERVAL: f32 = 0.5;
+/// The most paths worked out in a single frame, so rooms full of enemies spread the work out
+const MAX_PLANS_PER_FRAME: usize = 16;
+/// How close an enemy has to get to a waypoint before heading for the next one
+const WAYPOINT_REACHED: f32 = 16.0;
+
+/// Which parts of the current room enemies can walk through
+#[derive(Debug, Default, Resource)]
+pub struct NavGrid {
+    /// Bottom left corner of the grid
+    origin: Vec2,
+    width: usize,
+    height: usize,
+    blocked: Vec<bool>,
+    /// Where each obstacle was when the grid was built, in the same order as the room's
+    /// obstacles
+    positions: Vec<Vec2>,
+}
+
+impl NavGrid {
+    /// Builds the grid for a room, blocking off every obstacle that hasn't been destroyed
+    pub fn new(rect: Rect, obstacles: &[super::ObstacleState]) -> Self {
+        let width = (rect.width() / CELL_SIZE).ceil().max(1.0) as usize;
+        let height = (rect.height() / CELL_SIZE).ceil().max(1.0) as usize;
+        let mut grid = NavGrid {
+            origin: rect.min,
+            width,
+            height,
+            blocked: vec![false; width * height],
+            positions: obstacles.iter().map(|obstacle| obstacle.position).collect(),
+        };
+
+        let reach = super::obstacles::OBSTACLE_RADIUS + CLEARANCE;
+        for obstacle in obstacles.iter().filter(|obstacle| !obstacle.destroyed) {
+            let half_height = Vec2::new(0.0, super::obstacles::OBSTACLE_HALF_HEIGHT);
+            let (top, bottom) = (
+                obstacle.position + half_height,
+                obstacle.position - half_height,
+            );
+            let (min_x, min_y) = grid.cell(bottom - Vec2::splat(reach));
+            let (max_x, max_y) = grid.cell(top + Vec2::splat(reach));
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    let center = grid.center(x, y);
+                    // distance to the straight part of the capsule
+                    let closest = Vec2::new(obstacle.position.x, center.y.clamp(bottom.y, top.y));
+                    if center.distance(closest) < reach {
+                        grid.blocked[y * width + x] = true;
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    /// The cell `pos` is in. Positions outside the grid are moved to the nearest edge
+    fn cell(&self, pos: Vec2) -> (usize, usize) {
+        let local = ((pos - self.origin) / CELL_SIZE).floor();
+        (
+            (local.x.max(0.0) as usize).min(self.width - 1),
+            (local.y.max(0.0) as usize).min(self.height - 1),
+        )
+    }
+
+    fn center(&self, x: usize, y: usize) -> Vec2 {
+        self.origin + (Vec2::new(x as f32, y as f32) + 0.5) * CELL_SIZE
+    }
+
+    fn is_blocked(&self, x: usize, y: usize) -> bool {
+        self.blocked[y * self.width + x]
+    }
+
+    /// Can something walk straight from `from` to `to`? The cell `from` is in never counts as
+    /// blocked, so enemies pressed up against an obstacle can still get away from it
+    fn clear_line(&self, from: Vec2, to: Vec2) -> bool {
+        if self.blocked.is_empty() {
+            return false;
+        }
+        let start = self.cell(from);
+        let steps = (from.distance(to) / (CELL_SIZE * 0.25)).ceil() as usize;
+        (1..=steps).all(|step| {
+            let (x, y) = self.cell(from.lerp(to, step as f32 / steps as f32));
+            (x, y) == start || !self.is_blocked(x, y)
+        })
+    }
+
+    /// Finds a way from `from` to `to` around the room's obstacles, using A*.
+    /// Returns the waypoints to head for in order, ending with `to`, or `None` if there's
+    /// no way through
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        // not built for a room yet
+        if self.blocked.is_empty() {
+            return None;
+        }
+        if self.clear_line(from, to) {
+            return Some(vec![to]);
+        }
+
+        let (start_x, start_y) = self.cell(from);
+        let (goal_x, goal_y) = self.cell(to);
+        let start = start_y * self.width + start_x;
+        let goal = goal_y * self.width + goal_x;
+        // octile distance, since the grid allows diagonal moves
+        let heuristic = |index: usize| {
+            let dx = (index % self.width).abs_diff(goal_x) as f32;
+            let dy = (index / self.width).abs_diff(goal_y) as f32;
+            dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)
+        };
+
+        let mut cost = vec![f32::INFINITY; self.blocked.len()];
+        let mut came_from = vec![usize::MAX; self.blocked.len()];
+        let mut open = BinaryHeap::new();
+        cost[start] = 0.0;
+        open.push(OpenCell {
+            estimate: heuristic(start),
+            index: start,
+        });
+
+        while let Some(OpenCell { estimate, index }) = open.pop() {
+            if index == goal {
+                break;
+            }
+            // a cheaper way here was already found after this one was queued
+            if estimate > cost[index] + heuristic(index) {
+                continue;
+            }
+
+            let (x, y) = (index % self.width, index / self.width);
+            for (dx, dy) in NEIGHBOURS {
+                let (Some(next_x), Some(next_y)) =
+                    (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                else {
+                    continue;
+                };
+                if next_x >= self.width || next_y >= self.height {
+                    continue;
+                }
+                let next = next_y * self.width + next_x;
+                // the player might be standing right next to an obstacle
+                if next != goal && self.is_blocked(next_x, next_y) {
+                    continue;
+                }
+                let diagonal = dx != 0 && dy != 0;
+                // don't cut corners around obstacles
+                if diagonal && (self.is_blocked(next_x, y) || self.is_blocked(x, next_y)) {
+                    continue;
+                }
+
+                let step = if diagonal {
+                    std::f32::consts::SQRT_2
+                } else {
+                    1.0
+                };
+                let next_cost = cost[index] + step;
+                if next_cost < cost[next] {
+                    cost[next] = next_cost;
+                    came_from[next] = index;
+                    open.push(OpenCell {
+                        estimate: next_cost + heuristic(next),
+                        index: next,
+                    });
+                }
+            }
+        }
+
+        if goal != start && came_from[goal] == usize::MAX {
+            return None;
+        }
+        let mut cells = vec![];
+        let mut index = goal;
+        while index != start {
+            cells.push(self.center(index % self.width, index / self.width));
+            index = came_from[index];
+        }
+        cells.reverse();
+        if let Some(last) = cells.last_mut() {
+            *last = to;
+        }
+
+        // skip every waypoint that can be walked past in a straight line
+        let mut waypoints = vec![];
+        let mut current = from;
+        let mut next = 0;
+        while next < cells.len() {
+            let mut furthest = next;
+            while furthest + 1 < cells.len() && self.clear_line(current, cells[furthest + 1]) {
+                furthest += 1;
+            }
+            current = cells[furthest];
+            waypoints.push(current);
+            next = furthest + 1;
+        }
+        Some(waypoints)
+    }
+}
+
+const NEIGHBOURS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A cell waiting to be looked at by `NavGrid::find_path`, cheapest estimate first
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OpenCell {
+    estimate: f32,
+    index: usize,
+}
+
+impl Eq for OpenCell {}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // `BinaryHeap` pops the largest first, so flip the comparison
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| self.index.cmp(&other.index))
+    }
+}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The way an enemy is taking around obstacles to wherever it's going, see `NavGrid`
+#[derive(Debug, Component)]
+pub struct NavPath {
+    waypoints: Vec<Vec2>,
+    /// Counts down to the next time the path gets worked out again
+    timer: Timer,
+}
+
+impl NavPath {
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        // spread enemies out, so they don't all plan in the same frame
+        let mut timer = Timer::from_seconds(REPLAN_INTERVAL, TimerMode::Once);
+        timer.set_elapsed(std::time::Duration::from_secs_f32(
+            rng.gen_range(0.0..REPLAN_INTERVAL),
+        ));
+        NavPath {
+            waypoints: vec![],
+            timer,
+        }
+    }
+
+    /// Where to head for next, if the enemy is following a path
+    pub fn next_waypoint(&self) -> Option<Vec2> {
+        self.waypoints.first().copied()
+    }
+
+    /// Is the enemy heading straight for wherever it's going, with nothing in the way?
+    pub fn is_direct(&self) -> bool {
+        self.waypoints.len() <= 1
+    }
+}
+
+pub fn build_nav_grid(
+    mut commands: Commands,
+    current_room: Res<super::CurrentRoom>,
+    room_state: Res<super::PersistentRoomState>,
+) {
+    let obstacles = room_state
+        .rooms
+        .get(&current_room.info.name)
+        .map(|state| state.obstacles.as_slice())
+        .unwrap_or_default();
+    commands.insert_resource(NavGrid::new(current_room.info.rect, obstacles));
+}
+
+/// Opens the grid back up where an obstacle was destroyed, and follows pushable obstacles
+/// around once they've been pushed far enough
+pub fn update_nav_grid(
+    mut removed: RemovedComponents<super::Obstacle>,
+    moved_query: Query<(), (Changed<GlobalTransform>, With<super::obstacles::Pushable>)>,
+    pushable_query: Query<
+        (&super::obstacles::ObstacleIndex, &GlobalTransform),
+        With<super::obstacles::Pushable>,
+    >,
+    mut nav_grid: ResMut<NavGrid>,
+    current_room: Res<super::CurrentRoom>,
+    room_state: Res<super::PersistentRoomState>,
+) {
+    let destroyed = removed.read().count() > 0;
+    // nudging an obstacle around inside its cells doesn't change where enemies can go
+    let pushed = !moved_query.is_empty()
+        && pushable_query.iter().any(|(index, transform)| {
+            nav_grid.positions.get(index.0).is_some_and(|position| {
+                position.distance(transform.translation().truncate()) >= CELL_SIZE * 0.5
+            })
+        });
+    if !destroyed && !pushed {
+        return;
+    }
+    let Some(state) = room_state.rooms.get(&current_room.info.name) else {
+        return;
+    };
+
+    // the room state only learns where pushed obstacles are once the player leaves
+    let mut obstacles = state.obstacles.clone();
+    for (index, transform) in pushable_query.iter() {
+        if let Some(obstacle) = obstacles.get_mut(index.0) {
+            obstacle.position = transform.translation().truncate();
+        }
+    }
+    *nav_grid = NavGrid::new(current_room.info.rect, &obstacles);
+}
+
+/// Works out new paths for enemies that are going somewhere every so often, and drops the
+/// waypoints they've reached
+pub fn plan_enemy_paths(
+    mut enemy_query: Query<(
+        &crate::enemy::EnemyState,
+        &crate::enemy::EnemyStats,
+        &GlobalTransform,
+        &mut NavPath,
+    )>,
+    player_query: Query<&GlobalTransform, With<crate::player::Player>>,
+    nav_grid: Res<NavGrid>,
+    time: Res<Time>,
+) {
+    use crate::behavior::Action;
+    let player_pos = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation().truncate());
+
+    let mut plans_left = MAX_PLANS_PER_FRAME;
+    for (enemy_state, stats, transform, mut nav_path) in enemy_query.iter_mut() {
+        let target = match stats.behavior_state(enemy_state).map(|state| &state.action) {
+            Some(Action::Chase | Action::Kite { .. }) => player_pos,
+            Some(Action::Search) => enemy_state.last_seen,
+            Some(Action::ReturnHome) => Some(enemy_state.home),
+            Some(Action::Patrol { points }) => points
+                .get(enemy_state.patrol_index)
+                .map(|point| enemy_state.home + *point),
+            Some(Action::Idle | Action::Wander | Action::Flee) | None => None,
+        };
+        let Some(target) = target else {
+            nav_path.waypoints.clear();
+            continue;
+        };
+        let enemy_pos = transform.translation().truncate();
+
+        // the target might have moved since the path was planned
+        if let Some(last) = nav_path.waypoints.last_mut() {
+            *last = target;
+        }
+        // keep the last one, so the enemy doesn't stop short of the target
+        while nav_path.waypoints.len() > 1
+            && nav_path.waypoints[0].distance(enemy_pos) < WAYPOINT_REACHED
+        {
+            nav_path.waypoints.remove(0);
+        }
+
+        nav_path.timer.tick(time.delta());
+        // enemies that miss out this frame stay due until there's room for them
+        if !nav_path.timer.finished() || plans_left == 0 {
+            continue;
+        }
+        plans_left -= 1;
+        nav_path.timer.reset();
+        nav_path.waypoints = nav_grid.find_path(enemy_pos, target).unwrap_or_default();
+    }
+}
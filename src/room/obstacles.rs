@@ -0,0 +1,249 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rand::seq::SliceRandom;
+
+/// Half the length of the straight part of an obstacle's capsule collider
+pub(super) const OBSTACLE_HALF_HEIGHT: f32 = 12.0;
+/// Radius of an obstacle's capsule collider
+pub(super) const OBSTACLE_RADIUS: f32 = 12.0;
+
+/// How an obstacle reacts to the player
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect, serde::Deserialize, serde::Serialize)]
+pub enum ObstacleKind {
+    /// Never moves and can't be destroyed
+    #[default]
+    Static,
+    /// Breaks once it's taken `health` damage, from the player running into it or from
+    /// reflected projectiles
+    Destructible { health: f32 },
+    /// Gets knocked back when the player runs into it. Heavier ones need more Pants to move
+    Pushable { mass: f32 },
+}
+
+impl ObstacleKind {
+    fn color(&self) -> Color {
+        match self {
+            ObstacleKind::Static => Color::WHITE,
+            ObstacleKind::Destructible { .. } => Color::srgb(1.0, 0.7, 0.6),
+            ObstacleKind::Pushable { .. } => Color::srgb(0.7, 0.8, 1.0),
+        }
+    }
+}
+
+/// One kind of obstacle that can appear in a room
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct ObstacleEntry {
+    pub kind: ObstacleKind,
+    /// How likely this kind is to be picked, relative to the room's other kinds
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// Picks the kind of a newly placed obstacle. Rooms without any entries only get static ones
+pub(super) fn pick_kind<R: rand::Rng + ?Sized>(
+    entries: &[ObstacleEntry],
+    rng: &mut R,
+) -> ObstacleKind {
+    entries
+        .choose_weighted(rng, |entry| entry.weight)
+        .map(|entry| entry.kind)
+        .unwrap_or_default()
+}
+
+/// Holds the index of the obstacle in its room's `RoomState::obstacles`
+#[derive(Debug, Component)]
+pub struct ObstacleIndex(pub usize);
+
+/// How much more damage a destructible obstacle can take
+#[derive(Debug, Component)]
+pub struct ObstacleHealth(f32);
+
+/// Marks an obstacle the player can knock around
+#[derive(Debug, Component)]
+pub struct Pushable;
+
+/// Spawns an obstacle that hasn't been destroyed this cycle
+pub(super) fn spawn_obstacle(
+    commands: &mut Commands,
+    current_room: &super::CurrentRoom,
+    index: usize,
+    state: &super::ObstacleState,
+) {
+    let mut obstacle = commands.spawn((
+        super::ObstacleBundle {
+            texture: current_room.assets.obstacle_texture.clone(),
+            sprite: Sprite {
+                color: state.kind.color(),
+                custom_size: Some(Vec2::new(32.0, 64.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(state.position.extend(0.0)),
+            collider: Collider::capsule_y(OBSTACLE_HALF_HEIGHT, OBSTACLE_RADIUS),
+            colision_groups: CollisionGroups::new(
+                crate::physics::COLLISION_GROUP_OBSTACLE,
+                crate::physics::COLLISION_GROUP_ENEMY | crate::physics::COLLISION_GROUP_PLAYER,
+            ),
+            ..Default::default()
+        },
+        ObstacleIndex(index),
+        Name::new("Obstacle"),
+    ));
+    match state.kind {
+        ObstacleKind::Static => {}
+        ObstacleKind::Destructible { health } => {
+            obstacle.insert((
+                ObstacleHealth(health),
+                ActiveEvents::COLLISION_EVENTS,
+                // reflected projectiles are kinematic, and this is fixed
+                ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+                CollisionGroups::new(
+                    crate::physics::COLLISION_GROUP_OBSTACLE,
+                    crate::physics::COLLISION_GROUP_ENEMY
+                        | crate::physics::COLLISION_GROUP_PLAYER
+                        | crate::physics::COLLISION_GROUP_REFLECTED_PROJECTILE,
+                ),
+            ));
+        }
+        ObstacleKind::Pushable { mass } => {
+            obstacle.insert((
+                Pushable,
+                RigidBody::Dynamic,
+                ColliderMassProperties::Density(0.0),
+                AdditionalMassProperties::MassProperties(MassProperties {
+                    mass,
+                    ..Default::default()
+                }),
+                Damping {
+                    linear_damping: 5.0,
+                    angular_damping: 0.0,
+                },
+                LockedAxes::ROTATION_LOCKED,
+                ExternalImpulse::default(),
+                // so walls and other obstacles stop it
+                CollisionGroups::new(
+                    crate::physics::COLLISION_GROUP_OBSTACLE,
+                    crate::physics::COLLISION_GROUP_ENEMY
+                        | crate::physics::COLLISION_GROUP_OBSTACLE
+                        | crate::physics::COLLISION_GROUP_PLAYER,
+                ),
+            ));
+        }
+    }
+}
+
+/// Damages destructible obstacles hit by the player or by reflected projectiles, and
+/// destroys them for the rest of the cycle once they run out of health
+pub fn damage_obstacles(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    mut obstacle_query: Query<(&ObstacleIndex, &mut ObstacleHealth)>,
+    player_query: Query<Entity, With<crate::player::Player>>,
+    projectile_query: Query<&crate::projectiles::Projectile, With<crate::projectiles::Reflected>>,
+    current_room: Res<super::CurrentRoom>,
+    mut room_state: ResMut<super::PersistentRoomState>,
+    player_skills: Res<crate::skills::PlayerSkills>,
+) {
+    for ev in collisions.read() {
+        let CollisionEvent::Started(e1, e2, _flags) = ev else {
+            // we only care about the `Started` events here
+            continue;
+        };
+
+        let (obstacle, other) = if obstacle_query.contains(*e1) {
+            (*e1, *e2)
+        } else {
+            (*e2, *e1)
+        };
+        let Ok((index, mut health)) = obstacle_query.get_mut(obstacle) else {
+            continue;
+        };
+        // it might have been hit more than once this frame
+        if health.0 <= 0.0 {
+            continue;
+        }
+
+        let damage = if player_query.contains(other) {
+            player_skills.attack_damage()
+        } else if let Ok(projectile) = projectile_query.get(other) {
+            commands.entity(other).despawn_recursive();
+            projectile.damage
+        } else {
+            continue;
+        };
+        health.0 -= damage;
+        if health.0 > 0.0 {
+            continue;
+        }
+
+        info!(
+            "damage_obstacles: obstacle {} in {} destroyed",
+            index.0, current_room.info.name
+        );
+        commands.entity(obstacle).despawn_recursive();
+        if let Some(state) = room_state
+            .rooms
+            .get_mut(&current_room.info.name)
+            .and_then(|state| state.obstacles.get_mut(index.0))
+        {
+            state.destroyed = true;
+        }
+    }
+}
+
+/// Knocks pushable obstacles away from the player, using the same knockback as melee attacks
+pub fn push_obstacles(
+    mut collisions: EventReader<CollisionEvent>,
+    mut obstacle_query: Query<(&mut ExternalImpulse, &GlobalTransform), With<Pushable>>,
+    player_query: Query<&GlobalTransform, With<crate::player::Player>>,
+    player_skills: Res<crate::skills::PlayerSkills>,
+) {
+    for ev in collisions.read() {
+        let CollisionEvent::Started(e1, e2, _flags) = ev else {
+            // we only care about the `Started` events here
+            continue;
+        };
+
+        let Ok(player_transform) = player_query.get(*e1).or(player_query.get(*e2)) else {
+            continue;
+        };
+        let obstacle = if obstacle_query.contains(*e1) {
+            *e1
+        } else {
+            *e2
+        };
+        let Ok((mut impulse, obstacle_transform)) = obstacle_query.get_mut(obstacle) else {
+            continue;
+        };
+
+        let player_pos = player_transform.translation().truncate();
+        let obstacle_pos = obstacle_transform.translation().truncate();
+        let dir = (obstacle_pos - player_pos).normalize_or_zero();
+        // the obstacle's rigid body already has its mass, so only scale by the player's here
+        impulse.impulse += dir * 300.0 * player_skills.mass();
+    }
+}
+
+/// Stores where pushable obstacles ended up, right before the player leaves the room
+pub fn record_obstacle_positions(
+    mut events: EventReader<super::ChangeRoom>,
+    obstacle_query: Query<(&ObstacleIndex, &GlobalTransform), With<Pushable>>,
+    current_room: Res<super::CurrentRoom>,
+    mut room_state: ResMut<super::PersistentRoomState>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    let Some(this_room_state) = room_state.rooms.get_mut(&current_room.info.name) else {
+        return;
+    };
+
+    for (index, transform) in obstacle_query.iter() {
+        if let Some(state) = this_room_state.obstacles.get_mut(index.0) {
+            state.position = transform.translation().truncate();
+        }
+    }
+}
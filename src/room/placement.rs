@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+/// How many random points are tried for each placement before loosening the spacing
+const MAX_ATTEMPTS: usize = 30;
+
+/// Scatters `count` points over `rect`, Poisson-disk style: no point lands within
+/// `min_distance` of another point or of anything in `occupied`, and no point lands inside
+/// one of the `exclusions` circles (given as center and radius).
+///
+/// If the room is too crowded to fit everything, the spacing is loosened until it does,
+/// so the number of points returned is always `count`. Only randomness from `rng` is used,
+/// so the result is deterministic for a given seed
+pub fn scatter<R: rand::Rng + ?Sized>(
+    rng: &mut R,
+    rect: Rect,
+    count: usize,
+    min_distance: f32,
+    occupied: &[Vec2],
+    exclusions: &[(Vec2, f32)],
+) -> Vec<Vec2> {
+    let rectangle = Rectangle {
+        half_size: rect.half_size(),
+    };
+
+    let mut placed: Vec<Vec2> = Vec::with_capacity(count);
+    let mut spacing = min_distance;
+    while placed.len() < count {
+        let mut found = None;
+        for _ in 0..MAX_ATTEMPTS {
+            let pos = rectangle.sample_interior(rng) + rect.center();
+            let excluded = exclusions
+                .iter()
+                .any(|(center, radius)| center.distance_squared(pos) < radius * radius);
+            let crowded = occupied
+                .iter()
+                .chain(placed.iter())
+                .any(|other| other.distance_squared(pos) < spacing * spacing);
+            if !excluded && !crowded {
+                found = Some(pos);
+                break;
+            }
+        }
+
+        match found {
+            Some(pos) => placed.push(pos),
+            None if spacing > 1.0 => {
+                // no room left at this spacing, squeeze things closer together
+                spacing *= 0.75;
+            }
+            None => {
+                // even the exclusion zones don't leave any space, so just put it anywhere
+                warn!("scatter: couldn't find an open position, placing one anyway");
+                placed.push(rectangle.sample_interior(rng) + rect.center());
+            }
+        }
+    }
+
+    placed
+}
@@ -0,0 +1,122 @@
+use bevy::{prelude::*, utils::HashSet};
+
+/// Something that has to be true before an exit can be used
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub enum ExitRequirement {
+    /// The boss of the room the exit is in has to be defeated this cycle
+    BossDefeated,
+    /// The given skill has to be unlocked
+    SkillUnlocked(crate::skills::Skill),
+    /// The given skill has to be at least the given level
+    SkillLevel(crate::skills::Skill, u64),
+    /// The given story flag has to be raised. `hint` is shown while it isn't, see
+    /// `BossStats::story_flag`
+    StoryFlag {
+        flag: String,
+        #[serde(default)]
+        hint: Option<String>,
+    },
+}
+
+/// Story progress flags raised so far, e.g. by defeating bosses
+#[derive(Debug, Default, Resource)]
+pub struct StoryFlags {
+    pub flags: HashSet<String>,
+}
+
+/// Why each exit of the current room is locked, in the same order as `RoomInfo::exits`.
+/// `None` means the exit can be used
+#[derive(Debug, Default, Resource)]
+pub struct ExitLocks {
+    pub reasons: Vec<Option<String>>,
+}
+
+impl ExitLocks {
+    pub fn is_locked(&self, exit: usize) -> bool {
+        self.reason(exit).is_some()
+    }
+
+    pub fn reason(&self, exit: usize) -> Option<&str> {
+        self.reasons.get(exit)?.as_deref()
+    }
+}
+
+/// Covers an exit while it's locked
+#[derive(Debug, Component)]
+pub struct ExitBarrier(pub usize);
+
+impl ExitRequirement {
+    /// Why this requirement isn't met, or `None` if it is
+    fn unmet_reason(
+        &self,
+        boss_defeated: bool,
+        player_skills: &crate::skills::PlayerSkills,
+        story_flags: &StoryFlags,
+    ) -> Option<String> {
+        match self {
+            ExitRequirement::BossDefeated => {
+                (!boss_defeated).then(|| "Defeat the boss first".to_string())
+            }
+            ExitRequirement::SkillUnlocked(skill) => {
+                (!player_skills.get_unlocked(*skill)).then(|| format!("Requires the {skill} skill"))
+            }
+            ExitRequirement::SkillLevel(skill, level) => (player_skills.get(*skill) < *level)
+                .then(|| format!("Requires {skill} level {level}")),
+            ExitRequirement::StoryFlag { flag, hint } => (!story_flags.flags.contains(flag))
+                .then(|| hint.clone().unwrap_or("Locked".to_string())),
+        }
+    }
+}
+
+pub fn update_exit_locks(
+    current_room: Res<super::CurrentRoom>,
+    room_state: Res<super::PersistentRoomState>,
+    player_skills: Res<crate::skills::PlayerSkills>,
+    story_flags: Res<StoryFlags>,
+    mut exit_locks: ResMut<ExitLocks>,
+) {
+    // a room without a boss counts as having its boss defeated
+    let boss_defeated = room_state
+        .rooms
+        .get(&current_room.info.name)
+        .map(|state| {
+            state
+                .spawners
+                .iter()
+                .filter(|s| s.ty == super::SpawnerType::Boss)
+                .all(|s| !s.active)
+        })
+        .unwrap_or(!current_room.info.boss);
+
+    let reasons: Vec<Option<String>> = current_room
+        .info
+        .exits
+        .iter()
+        .map(|exit| {
+            exit.requires.iter().find_map(|requirement| {
+                requirement.unmet_reason(boss_defeated, &player_skills, &story_flags)
+            })
+        })
+        .collect();
+
+    // only write when something changed, so change detection stays useful
+    if exit_locks.reasons != reasons {
+        exit_locks.reasons = reasons;
+    }
+}
+
+pub fn update_exit_barriers(
+    mut query: Query<(Ref<ExitBarrier>, &mut Visibility)>,
+    exit_locks: Res<ExitLocks>,
+) {
+    for (barrier, mut visibility) in query.iter_mut() {
+        if !exit_locks.is_changed() && !barrier.is_added() {
+            continue;
+        }
+        *visibility = if exit_locks.is_locked(barrier.0) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
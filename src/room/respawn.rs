@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+/// When a room's defeated enemies come back during a cycle. Bosses never come back
+#[derive(Debug, Default, Clone, Copy, Reflect, serde::Deserialize)]
+pub enum RespawnPolicy {
+    /// Defeated enemies stay defeated for the rest of the cycle
+    #[default]
+    Never,
+    /// Defeated enemies come back once the player has spent `seconds` in other rooms
+    AfterAway { seconds: f32 },
+    /// Defeated enemies come back every time the room is entered
+    OnReenter,
+    /// While the player is in the room, a defeated enemy comes back every `interval` seconds
+    Trickle { interval: f32 },
+}
+
+/// Brings back the enemies of a room that's being entered again, if its policy says so
+pub(super) fn respawn_on_entry(room_state: &mut super::RoomState, policy: RespawnPolicy) {
+    let respawn = match policy {
+        RespawnPolicy::Never | RespawnPolicy::Trickle { .. } => false,
+        RespawnPolicy::AfterAway { seconds } => room_state.time_away >= seconds,
+        RespawnPolicy::OnReenter => true,
+    };
+    room_state.time_away = 0.0;
+    if !respawn {
+        return;
+    }
+
+    let mut count = 0;
+    for spawner in room_state.spawners.iter_mut() {
+        if !spawner.active && spawner.ty != super::SpawnerType::Boss {
+            spawner.active = true;
+            spawner.snapshot = None;
+            count += 1;
+        }
+    }
+    if count > 0 {
+        room_state.cleared = false;
+    }
+    info!("respawn_on_entry: respawned {count} enemies");
+}
+
+/// Counts how long the player has been away from every other room
+pub fn tick_time_away(
+    current_room: Res<super::CurrentRoom>,
+    mut room_state: ResMut<super::PersistentRoomState>,
+    time: Res<Time>,
+) {
+    for (name, state) in room_state.rooms.iter_mut() {
+        if *name != current_room.info.name {
+            state.time_away += time.delta_seconds();
+        }
+    }
+}
+
+/// Brings defeated enemies back one at a time in rooms with `RespawnPolicy::Trickle`
+pub fn trickle_respawn(
+    mut commands: Commands,
+    mut timer: Local<Timer>,
+    current_room: Res<super::CurrentRoom>,
+    mut room_state: ResMut<super::PersistentRoomState>,
+    spawner_query: Query<(&super::Spawner, &Transform)>,
+    player_query: Query<&GlobalTransform, With<crate::player::Player>>,
+    mut rng: ResMut<crate::rand::GlobalRng>,
+    time: Res<Time>,
+) {
+    let RespawnPolicy::Trickle { interval } = current_room.info.respawn else {
+        return;
+    };
+    // start counting again whenever the room changes
+    if current_room.is_changed() {
+        *timer = Timer::from_seconds(interval, TimerMode::Repeating);
+    }
+    timer.tick(time.delta());
+    if !timer.just_finished() {
+        return;
+    }
+
+    let Some(this_room_state) = room_state.rooms.get_mut(&current_room.info.name) else {
+        return;
+    };
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation().truncate();
+
+    // don't spawn anything right on top of the player
+    let candidates: Vec<_> = spawner_query
+        .iter()
+        .filter(|(spawner, transform)| {
+            spawner.ty != super::SpawnerType::Boss
+                && this_room_state
+                    .spawners
+                    .get(spawner.index)
+                    .is_some_and(|state| !state.active)
+                && transform.translation.truncate().distance(player_pos)
+                    >= current_room.info.safe_radius
+        })
+        .collect();
+    let Some((spawner, transform)) = candidates.choose(rng.as_mut()) else {
+        return;
+    };
+
+    info!(
+        "trickle_respawn: respawning spawner {} in {}",
+        spawner.index, current_room.info.name
+    );
+    this_room_state.spawners[spawner.index].active = true;
+    this_room_state.spawners[spawner.index].snapshot = None;
+    this_room_state.cleared = false;
+    super::spawn::spawn_enemy(
+        &mut commands,
+        &current_room,
+        Some(spawner.index),
+        spawner.ty,
+        **transform,
+        None,
+        rng.as_mut(),
+    );
+}
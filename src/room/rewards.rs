@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+/// What the player gets for clearing a room
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub enum RoomReward {
+    /// Gives `xp` to each of the given skills
+    Xp {
+        skills: Vec<crate::skills::Skill>,
+        xp: f32,
+    },
+    /// Restores all of the player's health
+    FullHeal,
+    /// Spawns a chest at `position`, or the center of the room if it isn't given.
+    /// Opening it gives `xp` to one of the player's unlocked skills
+    Chest {
+        #[serde(default)]
+        position: Option<Vec2>,
+        xp: f32,
+    },
+}
+
+impl RoomReward {
+    /// A short description for the room cleared banner
+    pub fn describe(&self) -> String {
+        match self {
+            RoomReward::Xp { skills, xp } => skills
+                .iter()
+                .map(|skill| format!("+{xp} {skill} XP"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            RoomReward::FullHeal => "Health restored".to_string(),
+            RoomReward::Chest { .. } => "A chest appeared".to_string(),
+        }
+    }
+}
+
+/// Every spawner in a room has been defeated. Only sent once per room per cycle, and never for
+/// rooms without spawners
+#[derive(Debug, Clone, Event)]
+pub struct RoomClearedEvent {
+    pub room: String,
+}
+
+/// Gives its room's reward when the player walks up to it
+#[derive(Debug, Component)]
+pub struct Chest {
+    xp: f32,
+}
+
+/// How close the player has to get to a chest to open it
+const CHEST_RADIUS: f32 = 24.0;
+
+fn chest_position(info: &super::RoomInfo) -> Option<(Vec2, f32)> {
+    match info.reward {
+        Some(RoomReward::Chest { position, xp }) => {
+            Some((position.unwrap_or(info.rect.center()), xp))
+        }
+        _ => None,
+    }
+}
+
+fn spawn_chest(commands: &mut Commands, position: Vec2, xp: f32) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.55, 0.35, 0.15),
+                custom_size: Some(Vec2::new(28.0, 20.0)),
+                ..Default::default()
+            },
+            // above hazards, below characters
+            transform: Transform::from_translation(position.extend(-3.0)),
+            ..Default::default()
+        },
+        Chest { xp },
+        super::RoomObject,
+        Name::new("Chest"),
+    ));
+}
+
+/// Puts back the chest of a room that was cleared earlier this cycle, if it wasn't opened
+pub fn spawn_unclaimed_chest(
+    mut commands: Commands,
+    current_room: Res<super::CurrentRoom>,
+    room_state: Res<super::PersistentRoomState>,
+) {
+    let Some(this_room_state) = room_state.rooms.get(&current_room.info.name) else {
+        return;
+    };
+    if !this_room_state.cleared || this_room_state.reward_claimed {
+        return;
+    }
+    if let Some((position, xp)) = chest_position(&current_room.info) {
+        spawn_chest(&mut commands, position, xp);
+    }
+}
+
+pub fn check_room_cleared(
+    current_room: Res<super::CurrentRoom>,
+    mut room_state: ResMut<super::PersistentRoomState>,
+    mut writer: EventWriter<RoomClearedEvent>,
+    // enemies summoned by a boss, which don't come from a spawner
+    summoned_query: Query<(), (With<crate::enemy::Enemy>, Without<super::SpawnerIndex>)>,
+) {
+    let Some(this_room_state) = room_state.rooms.get(&current_room.info.name) else {
+        return;
+    };
+    if this_room_state.cleared
+        || this_room_state.spawners.is_empty()
+        || this_room_state.spawners.iter().any(|s| s.active)
+        || !summoned_query.is_empty()
+    {
+        return;
+    }
+
+    info!("check_room_cleared: {} cleared", current_room.info.name);
+    if let Some(this_room_state) = room_state.rooms.get_mut(&current_room.info.name) {
+        this_room_state.cleared = true;
+    }
+    writer.send(RoomClearedEvent {
+        room: current_room.info.name.clone(),
+    });
+}
+
+pub fn grant_room_rewards(
+    mut commands: Commands,
+    mut events: EventReader<RoomClearedEvent>,
+    current_room: Res<super::CurrentRoom>,
+    mut room_state: ResMut<super::PersistentRoomState>,
+    mut player_health: ResMut<crate::player::PlayerHealth>,
+    mut xp_events: EventWriter<crate::skills::SkillXpEvent>,
+    chest_query: Query<(), With<Chest>>,
+) {
+    for RoomClearedEvent { room } in events.read() {
+        if *room != current_room.info.name {
+            continue;
+        }
+        let Some(reward) = current_room.info.reward.as_ref() else {
+            continue;
+        };
+        // clearing a room again after its enemies respawned doesn't give out more rewards
+        let already_given = room_state
+            .rooms
+            .get(room)
+            .is_some_and(|state| state.reward_claimed);
+        if already_given || !chest_query.is_empty() {
+            continue;
+        }
+        info!("grant_room_rewards: {room} rewards {reward:?}");
+
+        let claimed = match reward {
+            RoomReward::Xp { skills, xp } => {
+                xp_events.send_batch(skills.iter().map(|skill| crate::skills::SkillXpEvent {
+                    skill: *skill,
+                    xp: *xp,
+                }));
+                true
+            }
+            RoomReward::FullHeal => {
+                if !player_health.dead {
+                    player_health.current = player_health.maximum;
+                }
+                true
+            }
+            RoomReward::Chest { .. } => {
+                if let Some((position, xp)) = chest_position(&current_room.info) {
+                    spawn_chest(&mut commands, position, xp);
+                }
+                // claimed once it's opened
+                false
+            }
+        };
+        if let Some(this_room_state) = room_state.rooms.get_mut(room) {
+            this_room_state.reward_claimed = claimed;
+        }
+    }
+}
+
+pub fn open_chests(
+    mut commands: Commands,
+    chest_query: Query<(Entity, &GlobalTransform, &Chest)>,
+    player_query: Query<(Entity, &GlobalTransform), With<crate::player::Player>>,
+    current_room: Res<super::CurrentRoom>,
+    mut room_state: ResMut<super::PersistentRoomState>,
+    player_skills: Res<crate::skills::PlayerSkills>,
+    mut rng: ResMut<crate::rand::GlobalRng>,
+    mut xp_events: EventWriter<crate::skills::SkillXpEvent>,
+) {
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation().truncate();
+
+    for (entity, transform, chest) in chest_query.iter() {
+        if transform.translation().truncate().distance(player_pos) > CHEST_RADIUS {
+            continue;
+        }
+
+        let unlocked: Vec<_> = crate::skills::Skill::iter()
+            .filter(|skill| player_skills.get_unlocked(*skill))
+            .collect();
+        // armor is always unlocked, but don't lose the reward if it somehow isn't
+        let skill = unlocked
+            .choose(rng.as_mut())
+            .copied()
+            .unwrap_or(crate::skills::Skill::Armor);
+        info!("open_chests: chest gives {} {skill} xp", chest.xp);
+        xp_events.send(crate::skills::SkillXpEvent {
+            skill,
+            xp: chest.xp,
+        });
+
+        let floating_text = commands
+            .spawn((
+                SpatialBundle {
+                    transform: Transform::from_xyz(0.0, 16.0, 0.0),
+                    ..Default::default()
+                },
+                crate::text::TextMarker {
+                    color: Some(bevy::color::palettes::css::GOLD.into()),
+                    font_size: 18.0,
+                    text: format!("+{} {skill} XP", chest.xp),
+                    ..Default::default()
+                },
+                crate::text::FloatingText {
+                    timer: Timer::from_seconds(1.0, TimerMode::Once),
+                    velocity: Vec2::new(0.0, 16.0),
+                },
+                Name::new("Chest Floating Text"),
+            ))
+            .id();
+        commands.entity(player).add_child(floating_text);
+        commands.entity(entity).despawn_recursive();
+
+        if let Some(this_room_state) = room_state.rooms.get_mut(&current_room.info.name) {
+            this_room_state.reward_claimed = true;
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+/// One kind of enemy that can appear in a room
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct RosterEntry {
+    /// Path to the `*.enemy.ron` file with this enemy's stats
+    pub stats: String,
+    /// Path to this enemy's sprite
+    pub texture: String,
+    /// Path to the sprite for this enemy's projectiles. Falls back to the room's `projectile`
+    #[serde(default)]
+    pub projectile: Option<String>,
+    /// How likely this enemy is to be picked, relative to the rest of the roster
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    /// At least this many are always spawned. The roster's minimums have to fit in the room's
+    /// `num_enemies`
+    #[serde(default)]
+    pub min: usize,
+    /// No more than this many are spawned
+    #[serde(default)]
+    pub max: Option<usize>,
+    /// Handles to the files above, filled in when the room is loaded
+    #[serde(skip)]
+    pub handles: RosterHandles,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Default, Clone, Reflect)]
+pub struct RosterHandles {
+    pub stats: Handle<crate::enemy::EnemyStats>,
+    pub texture: Handle<Image>,
+    pub projectile: Option<Handle<Image>>,
+}
+
+/// A roster entry with everything resolved, ready to be spawned
+#[derive(Debug, Clone)]
+pub struct RosterEnemy {
+    pub stats: crate::enemy::EnemyStats,
+    pub texture: Handle<Image>,
+    pub projectile: Handle<Image>,
+}
+
+/// Picks which roster entries the randomly placed spawners of a room use.
+///
+/// `placed` holds how many of each entry have already been placed by hand. Every entry
+/// gets topped up to its `min` first, then the rest are picked by weight until the room
+/// has `num_enemies` in total or every entry has reached its `max`
+pub fn pick_entries<R: rand::Rng + ?Sized>(
+    roster: &[RosterEntry],
+    num_enemies: usize,
+    placed: &[usize],
+    rng: &mut R,
+) -> Vec<usize> {
+    let mut counts = placed.to_vec();
+    counts.resize(roster.len(), 0);
+
+    let mut picks = Vec::new();
+    for (index, entry) in roster.iter().enumerate() {
+        while counts[index] < entry.min {
+            counts[index] += 1;
+            picks.push(index);
+        }
+    }
+
+    while counts.iter().sum::<usize>() < num_enemies {
+        let candidates: Vec<usize> = (0..roster.len())
+            .filter(|&index| {
+                roster[index].weight > 0.0
+                    && roster[index].max.is_none_or(|max| counts[index] < max)
+            })
+            .collect();
+        let Ok(&index) = candidates.choose_weighted(rng, |&index| roster[index].weight) else {
+            // every entry is full
+            break;
+        };
+        counts[index] += 1;
+        picks.push(index);
+    }
+
+    picks
+}
@@ -1,7 +1,6 @@
 use bevy::prelude::*;
 use bevy_math::vec2;
 use bevy_rapier2d::prelude::*;
-use rand::prelude::Distribution;
 
 pub fn spawn_enemies(
     mut commands: Commands,
@@ -16,6 +15,7 @@ pub fn spawn_enemies(
             continue;
         }
 
+        let mut snapshot = None;
         if let Some(room_state) = room_state.rooms.get(&current_room.info.name) {
             let Some(spawner) = room_state.spawners.get(spawner.index) else {
                 error!("spawn_enemies: {:?} present in PersistentRoomState map, but list doesn't contain index {:?}", current_room.info.name, spawner.index);
@@ -26,97 +26,289 @@ pub fn spawn_enemies(
                 // This spawner's enemy has already been killed this cycle
                 continue;
             }
+            snapshot = spawner.snapshot.as_ref();
         }
 
-        let (texture, stats, boss_stats) = match spawner.ty {
-            super::SpawnerType::Melee => (
-                current_room.assets.melee_enemy_texture.clone(),
-                &current_room.melee_enemy_stats,
-                None,
-            ),
-            super::SpawnerType::Ranged => (
-                current_room.assets.ranged_enemy_texture.clone(),
-                &current_room.ranged_enemy_stats,
+        spawn_enemy(
+            &mut commands,
+            &current_room,
+            Some(spawner.index),
+            spawner.ty,
+            *transform,
+            snapshot,
+            rng.as_mut(),
+        );
+    }
+
+    next_state.set(crate::states::GameState::InGame);
+}
+
+/// Spawns the enemy for the spawner at `index` in the current room's state, or one that
+/// doesn't belong to any spawner, like a boss's summons.
+/// With a snapshot, the enemy picks up where it was left instead of starting fresh at `transform`
+pub(super) fn spawn_enemy(
+    commands: &mut Commands,
+    current_room: &super::CurrentRoom,
+    index: Option<usize>,
+    ty: super::SpawnerType,
+    mut transform: Transform,
+    snapshot: Option<&super::EnemySnapshot>,
+    rng: &mut crate::rand::GlobalRng,
+) {
+    let (texture, projectile, stats, boss_stats) = match ty {
+        super::SpawnerType::Roster(roster_index) => {
+            let Some(enemy) = current_room.roster.get(roster_index) else {
+                error!(
+                    "spawn_enemy: {} has no roster entry with index {}",
+                    current_room.info.name, roster_index
+                );
+                return;
+            };
+            (
+                enemy.texture.clone(),
+                enemy.projectile.clone(),
+                &enemy.stats,
                 None,
-            ),
-            super::SpawnerType::Boss => {
-                let Some(boss_stats) = &current_room.boss_stats else {
-                    error!("spawn_enemies: room contains a boss-variant spawner, but this room doesn't have a boss defined");
-                    continue;
-                };
-                let texture = match boss_stats.stats.enemy_type {
-                    crate::enemy::EnemyType::Melee { .. } => {
-                        current_room.assets.melee_enemy_texture.clone()
-                    }
-                    crate::enemy::EnemyType::Ranged { .. } => {
-                        current_room.assets.ranged_enemy_texture.clone()
-                    }
-                };
-                (texture, &boss_stats.stats, Some(boss_stats.clone()))
-            }
+            )
+        }
+        super::SpawnerType::Boss => {
+            let Some(boss_stats) = &current_room.boss_stats else {
+                error!("spawn_enemy: room contains a boss-variant spawner, but this room doesn't have a boss defined");
+                return;
+            };
+            let (texture, projectile) = boss_sprites(current_room, &boss_stats.stats);
+            (
+                texture,
+                projectile,
+                &boss_stats.stats,
+                Some(boss_stats.clone()),
+            )
+        }
+    };
+
+    let scale = boss_stats.as_ref().map(|bs| bs.scale).unwrap_or(1.0);
+    let mut health = crate::enemy::EnemyHealth::new(stats.health);
+    let mut enemy_state = crate::enemy::EnemyState {
+        home: transform.translation.truncate(),
+        ..Default::default()
+    };
+    if let Some(snapshot) = snapshot {
+        health.current = f32::min(snapshot.health, health.maximum);
+        transform.translation = snapshot.position.extend(transform.translation.z);
+        enemy_state = crate::enemy::EnemyState {
+            home: enemy_state.home,
+            ..snapshot.state
         };
+    }
 
-        let scale = boss_stats.as_ref().map(|bs| bs.scale).unwrap_or(1.0);
+    // bosses come back in whichever phase they'd reached
+    let phase = boss_stats
+        .as_ref()
+        .and_then(|boss_stats| boss_stats.phase_at(health.current / health.maximum));
+    let mut stats = match &boss_stats {
+        Some(boss_stats) => boss_stats.phase_stats(phase),
+        None => stats.clone(),
+    };
+    stats
+        .behavior
+        .get_or_insert_with(|| crate::behavior::Behavior::default_for(stats.search_time));
 
-        let mut spawned_enemy = commands.spawn(SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(vec2(32.0, 32.0) * scale),
+    let mut spawned_enemy = commands.spawn(SpriteBundle {
+        sprite: Sprite {
+            custom_size: Some(vec2(32.0, 32.0) * scale),
+            ..Default::default()
+        },
+        texture,
+        ..Default::default()
+    });
+    spawned_enemy
+        .insert((
+            crate::character_controller::CharacterController {
+                acceleration: 10.0,
+                max_speed: stats.speed,
                 ..Default::default()
             },
-            texture,
-            ..Default::default()
-        });
-        spawned_enemy
-            .insert((
-                crate::character_controller::CharacterController {
-                    acceleration: 10.0,
-                    max_speed: stats.speed,
-                    ..Default::default()
-                },
-                stats.clone(),
-                crate::enemy::EnemyHealth::new(stats.health),
-                crate::enemy::Enemy,
-                crate::enemy::EnemyState::default(),
-                RigidBody::Dynamic,
-                Collider::ball(16.0 * scale),
-                ColliderMassProperties::Density(0.0),
-                AdditionalMassProperties::MassProperties(MassProperties {
-                    mass: 1.0,
-                    ..Default::default()
-                }),
-                Velocity::default(),
-                ExternalImpulse::default(),
-                ActiveEvents::COLLISION_EVENTS,
-                transform.clone(),
-                super::SpawnerIndex(spawner.index),
-                // So the enemy will be despawned when we change room
-                crate::room::RoomObject,
-            ))
-            .insert((
-                crate::enemy::WanderState::new(2.5, 4.0, rng.as_mut()),
-                CollisionGroups::new(
-                    crate::physics::COLLISION_GROUP_ENEMY,
-                    crate::physics::COLLISION_GROUP_ENEMY
-                        | crate::physics::COLLISION_GROUP_OBSTACLE
-                        | crate::physics::COLLISION_GROUP_PLAYER
-                        | crate::physics::COLLISION_GROUP_REFLECTED_PROJECTILE,
-                ),
-                Name::new("Enemy"),
-            ));
+            stats.clone(),
+            health,
+            crate::enemy::Enemy,
+            enemy_state,
+            RigidBody::Dynamic,
+            Collider::ball(16.0 * scale),
+            ColliderMassProperties::Density(0.0),
+            AdditionalMassProperties::MassProperties(MassProperties {
+                mass: 1.0,
+                ..Default::default()
+            }),
+            Velocity::default(),
+            ExternalImpulse::default(),
+            ActiveEvents::COLLISION_EVENTS,
+            transform,
+            // So the enemy will be despawned when we change room
+            crate::room::RoomObject,
+        ))
+        .insert((
+            crate::projectiles::ProjectileSprite(projectile),
+            crate::enemy::WanderState::new(2.5, 4.0, rng),
+            crate::enemy::Facing::new(&stats),
+            super::NavPath::new(rng),
+            CollisionGroups::new(
+                crate::physics::COLLISION_GROUP_ENEMY,
+                crate::physics::COLLISION_GROUP_ENEMY
+                    | crate::physics::COLLISION_GROUP_OBSTACLE
+                    | crate::physics::COLLISION_GROUP_PLAYER
+                    | crate::physics::COLLISION_GROUP_REFLECTED_PROJECTILE,
+            ),
+            Name::new("Enemy"),
+        ));
 
-        if let Some(boss_stats) = boss_stats.as_ref() {
-            spawned_enemy.insert((boss_stats.clone(), crate::enemy::Boss, Name::new("Boss")));
-            if boss_stats.name == "The Wizard" {
-                spawned_enemy.insert(crate::enemy::FinalBoss);
+    if let Some(index) = index {
+        spawned_enemy.insert(super::SpawnerIndex(index));
+    }
+    if let Some(boss_stats) = boss_stats.as_ref() {
+        spawned_enemy.insert((
+            boss_stats.clone(),
+            crate::enemy::Boss,
+            crate::boss::BossPhaseIndex(phase),
+            Name::new("Boss"),
+        ));
+        if boss_stats.name == "The Wizard" {
+            spawned_enemy.insert(crate::enemy::FinalBoss);
+        }
+    }
+}
+
+/// The sprites for the boss and its projectiles. If the room doesn't give the boss its own
+/// sprite, it borrows one from a roster enemy of the same type
+fn boss_sprites(
+    current_room: &super::CurrentRoom,
+    boss_stats: &crate::enemy::EnemyStats,
+) -> (Handle<Image>, Handle<Image>) {
+    let same_type = |enemy: &&super::RosterEnemy| {
+        std::mem::discriminant(&enemy.stats.enemy_type)
+            == std::mem::discriminant(&boss_stats.enemy_type)
+    };
+    let lookalike = current_room
+        .roster
+        .iter()
+        .find(same_type)
+        .or(current_room.roster.first());
+
+    let texture = match (&current_room.assets.boss_texture, lookalike) {
+        (Some(texture), _) => texture.clone(),
+        (None, Some(enemy)) => enemy.texture.clone(),
+        (None, None) => {
+            warn!(
+                "boss_sprites: {} has no `boss_texture` and no roster to borrow one from",
+                current_room.info.name
+            );
+            Handle::default()
+        }
+    };
+    let projectile = match lookalike {
+        Some(enemy) if current_room.assets.boss_texture.is_none() => enemy.projectile.clone(),
+        _ => current_room.assets.projectile.clone(),
+    };
+
+    (texture, projectile)
+}
+
+/// How far from a boss its summons appear, before scaling by the boss's size
+const SUMMON_DISTANCE: f32 = 48.0;
+
+/// Spawns the enemies a boss summons when it starts a new phase, in a ring around it.
+/// They don't belong to a spawner, so they're gone for good once the player leaves the room
+pub fn summon_adds(
+    mut commands: Commands,
+    mut events: EventReader<crate::boss::BossPhaseChanged>,
+    boss_query: Query<(&crate::enemy::BossStats, &GlobalTransform)>,
+    current_room: Res<super::CurrentRoom>,
+    mut rng: ResMut<crate::rand::GlobalRng>,
+) {
+    use rand::Rng as _;
+    for crate::boss::BossPhaseChanged { boss, phase } in events.read() {
+        let Ok((boss_stats, transform)) = boss_query.get(*boss) else {
+            continue;
+        };
+        let Some(phase) = boss_stats.phases.get(*phase) else {
+            continue;
+        };
+        let boss_pos = transform.translation().truncate();
+        let room_rect = current_room.info.rect.inflate(-16.0);
+
+        for summon in phase.summons.iter() {
+            let Some(roster_index) = current_room
+                .info
+                .roster
+                .iter()
+                .position(|entry| entry.stats == summon.enemy)
+            else {
+                warn!(
+                    "summon_adds: {} isn't in the roster of {}",
+                    summon.enemy, current_room.info.name
+                );
+                continue;
+            };
+            info!(
+                "summon_adds: {} summoned {} of {}",
+                boss_stats.name, summon.count, summon.enemy
+            );
+            let start = rng.gen_range(0.0..std::f32::consts::TAU);
+            for i in 0..summon.count {
+                let angle = start + std::f32::consts::TAU * i as f32 / summon.count as f32;
+                let pos = (boss_pos + Vec2::from_angle(angle) * SUMMON_DISTANCE * boss_stats.scale)
+                    .clamp(room_rect.min, room_rect.max);
+                spawn_enemy(
+                    &mut commands,
+                    &current_room,
+                    None,
+                    super::SpawnerType::Roster(roster_index),
+                    Transform::from_translation(pos.extend(0.0)),
+                    None,
+                    rng.as_mut(),
+                );
             }
         }
     }
+}
 
-    next_state.set(crate::states::GameState::InGame);
+/// Remembers how the enemies in the current room are doing, just before the player leaves it
+pub fn record_enemy_snapshots(
+    mut events: EventReader<super::ChangeRoom>,
+    enemy_query: Query<(
+        &super::SpawnerIndex,
+        &crate::enemy::EnemyHealth,
+        &GlobalTransform,
+        &crate::enemy::EnemyState,
+    )>,
+    current_room: Res<super::CurrentRoom>,
+    mut room_state: ResMut<super::PersistentRoomState>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    let Some(this_room_state) = room_state.rooms.get_mut(&current_room.info.name) else {
+        return;
+    };
+
+    let mut count = 0;
+    for (spawner_index, health, transform, state) in enemy_query.iter() {
+        let Some(spawner) = this_room_state.spawners.get_mut(spawner_index.0) else {
+            continue;
+        };
+        spawner.snapshot = Some(super::EnemySnapshot {
+            health: health.current,
+            position: transform.translation().truncate(),
+            state: *state,
+        });
+        count += 1;
+    }
+    info!(
+        "record_enemy_snapshots: recorded {count} enemies in {}",
+        current_room.info.name
+    );
 }
 
 pub fn destroy_room(mut commands: Commands, query: Query<Entity, With<super::RoomObject>>) {
-    // TODO: keep track of dead enemies and flag their spawners as inactive in the `PersistentRoomState` cache
     let mut count = 0;
     for e in query.iter() {
         commands.entity(e).despawn_recursive();
@@ -130,7 +322,8 @@ pub fn spawn_room(
     current_room: Res<super::CurrentRoom>,
     mut room_state: ResMut<super::PersistentRoomState>,
     mut rng: ResMut<crate::rand::GlobalRng>,
-    mut working: Local<Vec<Vec2>>,
+    player_spawn_pos: Option<Res<crate::player::PlayerSpawnPosition>>,
+    room_graph: Res<super::RoomGraph>,
 ) {
     // Floor
     commands.spawn((
@@ -151,202 +344,293 @@ pub fn spawn_room(
         Name::new("Floor"),
     ));
 
-    // Walls
-    const WALL_THICKNESS: f32 = 100.0;
-
+    // Walls and exits
     let room_rect = current_room.info.rect;
-    let top_left = room_rect.min;
-    let top_right = vec2(room_rect.max.x, room_rect.min.y);
-    let bottom_left = vec2(room_rect.min.x, room_rect.max.y);
-    let bottom_right = room_rect.max;
-
-    let wall_rects = [
-        // Left wall
-        Rect::from_corners(
-            top_left - vec2(WALL_THICKNESS, WALL_THICKNESS),
-            bottom_left + vec2(0.0, WALL_THICKNESS),
-        ),
-        // Top wall
-        Rect::from_corners(
-            bottom_left - vec2(WALL_THICKNESS, 0.0),
-            bottom_right + vec2(WALL_THICKNESS, WALL_THICKNESS),
-        ),
-        // Right wall
-        Rect::from_corners(
-            top_right - vec2(0.0, WALL_THICKNESS),
-            bottom_right + vec2(WALL_THICKNESS, WALL_THICKNESS),
-        ),
-        // Bottom wall
-        Rect::from_corners(
-            top_left - vec2(WALL_THICKNESS, WALL_THICKNESS),
-            top_right + vec2(WALL_THICKNESS, 0.0),
-        ),
+    let sides = [
+        super::CardinalDirection::North,
+        super::CardinalDirection::South,
+        super::CardinalDirection::East,
+        super::CardinalDirection::West,
     ];
-    for (i, rect) in wall_rects.into_iter().enumerate() {
-        let wall = match i {
-            0 => crate::room::Wall(crate::room::CardinalDirection::West),
-            1 => crate::room::Wall(crate::room::CardinalDirection::North),
-            2 => crate::room::Wall(crate::room::CardinalDirection::East),
-            3 => crate::room::Wall(crate::room::CardinalDirection::South),
-            _ => unreachable!(),
+    // exits that don't lead anywhere this cycle are walled off
+    let leads_somewhere = |index: usize| room_graph.link(&current_room.info.name, index).is_some();
+    for side in sides {
+        for (rect, exit_index) in wall_segments(room_rect, side, &current_room.info.exits) {
+            let exit_index = exit_index.filter(|index| leads_somewhere(*index));
+            let mut wall = commands.spawn((
+                TransformBundle {
+                    local: Transform::from_translation(rect.center().extend(0.0)),
+                    ..Default::default()
+                },
+                Collider::cuboid(rect.half_size().x, rect.half_size().y),
+                crate::room::RoomObject,
+                // obstacles only collide with walls if they can be pushed into them
+                CollisionGroups::new(
+                    crate::physics::COLLISION_GROUP_OBSTACLE,
+                    crate::physics::COLLISION_GROUP_ENEMY
+                        | crate::physics::COLLISION_GROUP_OBSTACLE
+                        | crate::physics::COLLISION_GROUP_PLAYER,
+                ),
+            ));
+            match exit_index {
+                Some(index) => wall.insert((super::Exit(index), Name::new("Exit"))),
+                None => wall.insert((super::Wall, Name::new("Wall"))),
+            };
+            if let Some(index) = exit_index {
+                spawn_exit_barrier(&mut commands, rect, index);
+            }
+        }
+    }
+
+    // Doors in the middle of the room
+    for (index, exit) in current_room.info.exits.iter().enumerate() {
+        let super::ExitLocation::Door { position, size } = exit.location else {
+            continue;
         };
+        if !leads_somewhere(index) {
+            continue;
+        }
         commands.spawn((
-            TransformBundle {
-                local: Transform::from_translation(rect.center().extend(0.0)),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: bevy::color::palettes::css::SADDLE_BROWN.into(),
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(-5.0)),
                 ..Default::default()
             },
-            Collider::cuboid(rect.half_size().x, rect.half_size().y),
-            wall,
+            Collider::cuboid(size.x / 2.0, size.y / 2.0),
+            super::Exit(index),
             crate::room::RoomObject,
             CollisionGroups::new(
                 crate::physics::COLLISION_GROUP_OBSTACLE,
                 crate::physics::COLLISION_GROUP_ENEMY | crate::physics::COLLISION_GROUP_PLAYER,
             ),
-            Name::new("Wall"),
+            Name::new("Door"),
         ));
+        spawn_exit_barrier(&mut commands, Rect::from_center_size(position, size), index);
     }
 
-    if let Some(room_state) = room_state.rooms.get(&current_room.info.name) {
-        info!(
-            "{} has been previously visited this cycle, spawning according to cached data",
-            current_room.info.name
-        );
-        // room state found, spawn things according to the cached data
-        for (index, spawner_state) in room_state.spawners.iter().enumerate() {
-            commands.spawn((
-                super::SpawnerBundle {
-                    transform: Transform::from_translation(spawner_state.position.extend(0.0)),
-                    spawner: super::Spawner {
-                        index,
-                        ty: spawner_state.ty,
-                        active: spawner_state.active,
-                    },
-                    ..Default::default()
-                },
-                Name::new("Spawner"),
-            ));
+    let this_room_state = match room_state.rooms.entry(current_room.info.name.clone()) {
+        bevy::utils::Entry::Occupied(entry) => {
+            info!(
+                "{} has been previously visited this cycle, spawning according to cached data",
+                current_room.info.name
+            );
+            let this_room_state = entry.into_mut();
+            super::respawn::respawn_on_entry(this_room_state, current_room.info.respawn);
+            this_room_state
         }
-        for obstacle_state in room_state.obstacles.iter() {
-            commands.spawn((
-                super::ObstacleBundle {
-                    texture: current_room.assets.obstacle_texture.clone(),
-                    sprite: Sprite {
-                        custom_size: Some(vec2(32.0, 64.0)),
-                        ..Default::default()
-                    },
-                    transform: Transform::from_translation(obstacle_state.position.extend(0.0)),
-                    collider: Collider::capsule_y(12.0, 12.0),
-                    colision_groups: CollisionGroups::new(
-                        crate::physics::COLLISION_GROUP_OBSTACLE,
-                        crate::physics::COLLISION_GROUP_ENEMY
-                            | crate::physics::COLLISION_GROUP_PLAYER,
-                    ),
-                    ..Default::default()
+        bevy::utils::Entry::Vacant(entry) => {
+            info!(
+                "{} hasn't been visited this cycle, spawning new entities",
+                current_room.info.name
+            );
+            // room state not found, place things freshly and cache the data
+            let entry_pos = player_spawn_pos
+                .map(|p| p.pos)
+                .unwrap_or(current_room.info.rect.center());
+            entry.insert(generate_room_state(
+                &current_room.info,
+                &room_graph,
+                entry_pos,
+                rng.as_mut(),
+            ))
+        }
+    };
+    for (index, spawner_state) in this_room_state.spawners.iter().enumerate() {
+        commands.spawn((
+            super::SpawnerBundle {
+                transform: Transform::from_translation(spawner_state.position.extend(0.0)),
+                global_transform: GlobalTransform::default(),
+                spawner: super::Spawner {
+                    index,
+                    ty: spawner_state.ty,
+                    active: spawner_state.active,
                 },
-                Name::new("Obstacle"),
-            ));
+                room_object: super::RoomObject,
+            },
+            Name::new("Spawner"),
+        ));
+    }
+    for (index, obstacle_state) in this_room_state.obstacles.iter().enumerate() {
+        if !obstacle_state.destroyed {
+            super::obstacles::spawn_obstacle(&mut commands, &current_room, index, obstacle_state);
         }
-    } else {
-        info!(
-            "{} hasn't been visited this cycle, spawning new entities",
-            current_room.info.name
-        );
-        // room state not found, spawn things freshly and cache the data
-        let spawning_rectangle = {
-            let room_rect = current_room.info.rect;
-            // don't spawn things on the outer edge of the room
-            Rectangle {
-                half_size: room_rect.half_size() * 0.80,
+    }
+}
+
+/// Places the spawners and obstacles for a room that hasn't been visited yet this cycle.
+///
+/// Anything listed in the room's layout is placed exactly where it says, and the rest are
+/// scattered randomly over whatever space the layout leaves open. Random placement keeps
+/// `spawn_spacing` away from everything else, and `safe_radius` away from `entry` and from
+/// every other place the player could enter the room, since the result is cached for the
+/// rest of the cycle
+fn generate_room_state(
+    info: &super::RoomInfo,
+    room_graph: &super::RoomGraph,
+    entry: Vec2,
+    rng: &mut crate::rand::GlobalRng,
+) -> super::RoomState {
+    let mut this_room_state = super::RoomState::default();
+
+    // hand-placed spawners and obstacles
+    if let Some(layout) = &info.layout {
+        for spawner in layout.spawners.iter() {
+            this_room_state.spawners.push(super::SpawnerState {
+                active: true,
+                position: spawner.position,
+                ty: spawner.ty,
+                snapshot: None,
+            });
+        }
+        for position in layout.obstacles.iter() {
+            this_room_state.obstacles.push(super::ObstacleState {
+                position: *position,
+                kind: super::obstacles::pick_kind(&info.obstacle_kinds, &mut *rng),
+                destroyed: false,
+            });
+        }
+    }
+
+    // work out how much is left for random placement
+    let mut placed = vec![0; info.roster.len()];
+    for spawner in this_room_state.spawners.iter() {
+        if let super::SpawnerType::Roster(index) = spawner.ty {
+            if let Some(count) = placed.get_mut(index) {
+                *count += 1;
             }
-        };
+        }
+    }
+    let mut types: Vec<super::SpawnerType> =
+        super::roster::pick_entries(&info.roster, info.num_enemies, &placed, &mut *rng)
+            .into_iter()
+            .map(super::SpawnerType::Roster)
+            .collect();
+    let has_boss = this_room_state
+        .spawners
+        .iter()
+        .any(|s| s.ty == super::SpawnerType::Boss);
+    if info.boss && !has_boss {
+        types.push(super::SpawnerType::Boss);
+    }
+    let num_obstacles = info
+        .num_obstacles
+        .saturating_sub(this_room_state.obstacles.len());
 
-        let mut this_room_state = super::RoomState::default();
+    // don't spawn things on the outer edge of the room
+    let spawning_rect =
+        Rect::from_center_half_size(info.rect.center(), info.rect.half_size() * 0.80);
 
-        let num_bosses = if current_room.info.boss { 1 } else { 0 };
+    // keep clear of everywhere the player might appear, including exits elsewhere that
+    // override where they arrive in here
+    let arrivals = room_graph
+        .links
+        .values()
+        .flatten()
+        .flatten()
+        .filter(|link| link.room == info.name)
+        .filter_map(|link| link.arrival);
+    let safe_zones: Vec<(Vec2, f32)> = std::iter::once(entry)
+        .chain(info.exits.iter().map(|exit| exit.arrival_point(info.rect)))
+        .chain(arrivals)
+        .map(|pos| (pos, info.safe_radius))
+        .collect();
 
-        // spawners:
-        working.clear();
-        working.extend(
-            spawning_rectangle
-                .interior_dist()
-                .sample_iter(rng.as_mut())
-                .take(
-                    current_room.info.num_melee_enemies
-                        + current_room.info.num_ranged_enemies
-                        + num_bosses,
-                ),
-        );
-        for (index, pos) in working.drain(..).enumerate() {
-            let ty = if index < current_room.info.num_melee_enemies {
-                super::SpawnerType::Melee
-            } else if index
-                < current_room.info.num_melee_enemies + current_room.info.num_ranged_enemies
-            {
-                super::SpawnerType::Ranged
-            } else {
-                super::SpawnerType::Boss
-            };
+    let occupied: Vec<Vec2> = this_room_state
+        .spawners
+        .iter()
+        .map(|s| s.position)
+        .chain(this_room_state.obstacles.iter().map(|o| o.position))
+        .collect();
+    let num_spawners = types.len();
+    let positions = super::placement::scatter(
+        rng,
+        spawning_rect,
+        num_spawners + num_obstacles,
+        info.spawn_spacing,
+        &occupied,
+        &safe_zones,
+    );
 
-            commands.spawn((
-                super::SpawnerBundle {
-                    transform: Transform::from_translation(pos.extend(0.0)),
-                    spawner: super::Spawner {
-                        index,
-                        ty,
-                        active: true,
-                    },
-                    ..Default::default()
-                },
-                Name::new("Spawner"),
-            ));
-            this_room_state.spawners.push(super::SpawnerState {
-                active: true,
+    for (index, pos) in positions.into_iter().enumerate() {
+        if index >= num_spawners {
+            this_room_state.obstacles.push(super::ObstacleState {
                 position: pos,
-                ty,
+                kind: super::obstacles::pick_kind(&info.obstacle_kinds, &mut *rng),
+                destroyed: false,
             });
+            continue;
         }
 
-        // obstacles:
-        working.clear();
-        working.extend(
-            spawning_rectangle
-                .interior_dist()
-                .sample_iter(rng.as_mut())
-                .take(current_room.info.num_obstacles),
-        );
-        for pos in working.drain(..) {
-            commands.spawn((
-                super::ObstacleBundle {
-                    texture: current_room.assets.obstacle_texture.clone(),
-                    sprite: Sprite {
-                        custom_size: Some(vec2(32.0, 64.0)),
-                        ..Default::default()
-                    },
-                    transform: Transform::from_translation(pos.extend(0.0)),
-                    collider: Collider::capsule_y(12.0, 12.0),
-                    colision_groups: CollisionGroups::new(
-                        crate::physics::COLLISION_GROUP_OBSTACLE,
-                        crate::physics::COLLISION_GROUP_ENEMY
-                            | crate::physics::COLLISION_GROUP_PLAYER,
-                    ),
-                    ..Default::default()
-                },
-                Name::new("Obstacle"),
-            ));
+        this_room_state.spawners.push(super::SpawnerState {
+            active: true,
+            position: pos,
+            ty: types[index],
+            snapshot: None,
+        });
+    }
 
-            this_room_state
-                .obstacles
-                .push(super::ObstacleState { position: pos });
-        }
+    this_room_state
+}
 
-        let None = room_state
-            .rooms
-            .insert(current_room.info.name.clone(), this_room_state)
-        else {
-            panic!(
-                "room_state map already has entry for {}, but we checked that it didn't!",
-                current_room.info.name
-            );
-        };
+/// Spawns the barrier shown over an exit while it's locked
+fn spawn_exit_barrier(commands: &mut Commands, rect: Rect, index: usize) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(0.4, 0.2, 0.6, 0.8),
+                custom_size: Some(rect.size()),
+                ..Default::default()
+            },
+            // above doors, below everything that moves
+            transform: Transform::from_translation(rect.center().extend(-4.0)),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        super::ExitBarrier(index),
+        super::RoomObject,
+        Name::new("Exit Barrier"),
+    ));
+}
+
+/// Splits one side of the room into solid sections of wall and exits.
+/// Exit sections are paired with the index of their exit in `exits`
+fn wall_segments(
+    room_rect: Rect,
+    side: super::CardinalDirection,
+    exits: &[super::RoomExit],
+) -> Vec<(Rect, Option<usize>)> {
+    use super::exits::WALL_THICKNESS;
+
+    let mut exit_ranges: Vec<(usize, f32, f32)> = exits
+        .iter()
+        .enumerate()
+        .filter_map(|(index, exit)| {
+            let (exit_side, from, to) = exit.wall_range(room_rect)?;
+            (exit_side == side && from < to).then_some((index, from, to))
+        })
+        .collect();
+    exit_ranges.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    // extend the wall past the corners so there aren't any gaps
+    let (lo, hi) = side.span(room_rect);
+    let (lo, hi) = (lo - WALL_THICKNESS, hi + WALL_THICKNESS);
+
+    let mut segments = Vec::new();
+    let mut cursor = lo;
+    for (index, from, to) in exit_ranges {
+        if from > cursor {
+            segments.push((side.wall_rect(room_rect, cursor, from), None));
+        }
+        segments.push((side.wall_rect(room_rect, from, to), Some(index)));
+        cursor = f32::max(cursor, to);
     }
+    if cursor < hi {
+        segments.push((side.wall_rect(room_rect, cursor, hi), None));
+    }
+
+    segments
 }
@@ -13,6 +13,19 @@ impl Plugin for SaveDataPlugin {
                     exited: crate::states::AppState::RoomLoading,
                 },
                 load_data,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: crate::states::GameState::RoomTransition,
+                    entered: crate::states::GameState::InGame,
+                },
+                save_cycle,
+            )
+            .add_systems(
+                Last,
+                save_cycle.run_if(
+                    on_event::<AppExit>().and_then(in_state(crate::states::GameState::InGame)),
+                ),
             );
     }
 }
@@ -48,14 +61,67 @@ fn load_data(mut commands: Commands, pkv_store: Res<PkvStore>) {
 }
 
 pub fn save_data(
+    mut commands: Commands,
+    mut pkv_store: ResMut<PkvStore>,
+    player_skills: Res<crate::skills::PlayerSkills>,
+    cycle_counter: Res<crate::cycles::CycleCounter>,
+    muted: Res<crate::audio::Muted>,
+    story_flags: Res<crate::room::StoryFlags>,
+    visited_rooms: Res<crate::map::VisitedRooms>,
+) {
+    let save_data = SaveData::from_resources(
+        &player_skills,
+        &cycle_counter,
+        &muted,
+        &story_flags,
+        &visited_rooms,
+    );
+    write_save_data(&mut pkv_store, &save_data);
+    // keep it around, so "Continue" picks up from here instead of from when the game started
+    commands.insert_resource(save_data);
+}
+
+/// Saves the game along with the cycle in progress, so it can be resumed from the current room
+fn save_cycle(
+    mut commands: Commands,
     mut pkv_store: ResMut<PkvStore>,
     player_skills: Res<crate::skills::PlayerSkills>,
     cycle_counter: Res<crate::cycles::CycleCounter>,
     muted: Res<crate::audio::Muted>,
+    story_flags: Res<crate::room::StoryFlags>,
+    visited_rooms: Res<crate::map::VisitedRooms>,
+    player_health: Res<crate::player::PlayerHealth>,
+    current_room: Res<crate::room::CurrentRoom>,
+    player_spawn_pos: Res<crate::player::PlayerSpawnPosition>,
+    room_state: Res<crate::room::PersistentRoomState>,
+    room_graph: Res<crate::room::RoomGraph>,
 ) {
-    let save_data = SaveData::from_resources(&player_skills, &cycle_counter, &muted);
+    let mut save_data = SaveData::from_resources(
+        &player_skills,
+        &cycle_counter,
+        &muted,
+        &story_flags,
+        &visited_rooms,
+    );
+    save_data.in_progress = Some(CycleSave {
+        room: current_room.info.name.clone(),
+        entry: player_spawn_pos.pos,
+        health: player_health.current,
+        graph_seed: room_graph.seed,
+        skills: crate::skills::Skill::iter()
+            .map(|skill| {
+                let (levels, xp) = player_skills.cycle_progress(skill);
+                SkillProgress { skill, levels, xp }
+            })
+            .collect(),
+        rooms: room_state.rooms.clone(),
+    });
+    write_save_data(&mut pkv_store, &save_data);
+    commands.insert_resource(save_data);
+}
 
-    let ron_string = match ron::ser::to_string(&save_data) {
+fn write_save_data(pkv_store: &mut PkvStore, save_data: &SaveData) {
+    let ron_string = match ron::ser::to_string(save_data) {
         Ok(string) => {
             info!("save_data: successfully encoded save data");
             string
@@ -82,6 +148,19 @@ pub struct SaveData {
     pub audio_muted: bool,
     pub cycles: u64,
 
+    /// Story flags raised so far, see `StoryFlags`
+    #[serde(default)]
+    pub story_flags: Vec<String>,
+
+    /// Every room visited so far, see `VisitedRooms`
+    #[serde(default)]
+    pub visited_rooms: Vec<String>,
+
+    /// The cycle that was being played when the game was saved, if it hasn't ended.
+    /// The skill levels below don't include its progress
+    #[serde(default)]
+    pub in_progress: Option<CycleSave>,
+
     pub armor_level: u64,
     pub armor_xp: f32,
     pub armor_unlocked: bool,
@@ -111,45 +190,82 @@ pub struct SaveData {
     pub speed_unlocked: bool,
 }
 
+/// Everything needed to pick a cycle back up where it was left off
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct CycleSave {
+    /// The room the player was in
+    pub room: String,
+    /// Where the player entered that room
+    pub entry: Vec2,
+    pub health: f32,
+    /// Seed the room graph was generated from, if it was generated
+    pub graph_seed: Option<u64>,
+    /// Skill progress made during the cycle
+    pub skills: Vec<SkillProgress>,
+    /// Spawners and obstacles of every room visited during the cycle
+    pub rooms: bevy::utils::HashMap<String, crate::room::RoomState>,
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub struct SkillProgress {
+    pub skill: crate::skills::Skill,
+    pub levels: u64,
+    pub xp: f32,
+}
+
+/// Inserted when "Continue" is picked with a cycle in progress, and removed once it's resumed
+#[derive(Debug, Resource)]
+pub struct ResumeCycle(pub CycleSave);
+
 impl SaveData {
     /// extracts the relevant data from the game state.
     ///
-    /// remember to call PlayerSkills::end_cycle before this
+    /// progress made during the current cycle isn't included, see `SaveData::in_progress`
     pub fn from_resources(
         player_skills: &crate::skills::PlayerSkills,
         cycle_counter: &crate::cycles::CycleCounter,
         muted: &crate::audio::Muted,
+        story_flags: &crate::room::StoryFlags,
+        visited_rooms: &crate::map::VisitedRooms,
     ) -> Self {
+        let mut story_flags: Vec<String> = story_flags.flags.iter().cloned().collect();
+        story_flags.sort();
+        let mut visited_rooms: Vec<String> = visited_rooms.rooms.iter().cloned().collect();
+        visited_rooms.sort();
+
         Self {
             audio_muted: muted.muted,
             cycles: cycle_counter.count,
+            story_flags,
+            visited_rooms,
+            in_progress: None,
 
-            armor_level: player_skills.get(crate::skills::Skill::Armor),
-            armor_xp: player_skills.get_xp(crate::skills::Skill::Armor),
+            armor_level: player_skills.get_highest(crate::skills::Skill::Armor),
+            armor_xp: player_skills.get_stored_xp(crate::skills::Skill::Armor),
             armor_unlocked: player_skills.get_unlocked(crate::skills::Skill::Armor),
 
-            sword_level: player_skills.get(crate::skills::Skill::Sword),
-            sword_xp: player_skills.get_xp(crate::skills::Skill::Sword),
+            sword_level: player_skills.get_highest(crate::skills::Skill::Sword),
+            sword_xp: player_skills.get_stored_xp(crate::skills::Skill::Sword),
             sword_unlocked: player_skills.get_unlocked(crate::skills::Skill::Sword),
 
-            shield_level: player_skills.get(crate::skills::Skill::Shield),
-            shield_xp: player_skills.get_xp(crate::skills::Skill::Shield),
+            shield_level: player_skills.get_highest(crate::skills::Skill::Shield),
+            shield_xp: player_skills.get_stored_xp(crate::skills::Skill::Shield),
             shield_unlocked: player_skills.get_unlocked(crate::skills::Skill::Shield),
 
-            pants_level: player_skills.get(crate::skills::Skill::Pants),
-            pants_xp: player_skills.get_xp(crate::skills::Skill::Pants),
+            pants_level: player_skills.get_highest(crate::skills::Skill::Pants),
+            pants_xp: player_skills.get_stored_xp(crate::skills::Skill::Pants),
             pants_unlocked: player_skills.get_unlocked(crate::skills::Skill::Pants),
 
-            mirror_level: player_skills.get(crate::skills::Skill::Mirror),
-            mirror_xp: player_skills.get_xp(crate::skills::Skill::Mirror),
+            mirror_level: player_skills.get_highest(crate::skills::Skill::Mirror),
+            mirror_xp: player_skills.get_stored_xp(crate::skills::Skill::Mirror),
             mirror_unlocked: player_skills.get_unlocked(crate::skills::Skill::Mirror),
 
-            healing_level: player_skills.get(crate::skills::Skill::Healing),
-            healing_xp: player_skills.get_xp(crate::skills::Skill::Healing),
+            healing_level: player_skills.get_highest(crate::skills::Skill::Healing),
+            healing_xp: player_skills.get_stored_xp(crate::skills::Skill::Healing),
             healing_unlocked: player_skills.get_unlocked(crate::skills::Skill::Healing),
 
-            speed_level: player_skills.get(crate::skills::Skill::Speed),
-            speed_xp: player_skills.get_xp(crate::skills::Skill::Speed),
+            speed_level: player_skills.get_highest(crate::skills::Skill::Speed),
+            speed_xp: player_skills.get_stored_xp(crate::skills::Skill::Speed),
             speed_unlocked: player_skills.get_unlocked(crate::skills::Skill::Speed),
         }
     }
@@ -160,6 +276,8 @@ impl SaveData {
         crate::skills::PlayerSkills,
         crate::cycles::CycleCounter,
         crate::audio::Muted,
+        crate::room::StoryFlags,
+        crate::map::VisitedRooms,
     ) {
         (
             crate::skills::PlayerSkills::from_save_data(self),
@@ -167,6 +285,12 @@ impl SaveData {
             crate::audio::Muted {
                 muted: self.audio_muted,
             },
+            crate::room::StoryFlags {
+                flags: self.story_flags.iter().cloned().collect(),
+            },
+            crate::map::VisitedRooms {
+                rooms: self.visited_rooms.iter().cloned().collect(),
+            },
         )
     }
 }
@@ -4,7 +4,7 @@ use bevy::prelude::*;
 
 use super::LevelUpEvent;
 
-#[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub enum Skill {
     Armor,
     Sword,
@@ -123,6 +123,22 @@ impl PlayerSkills {
         self.stored_xp[skill] + self.delta_xp[skill]
     }
 
+    /// Get the xp of a skill as of the beginning of the current cycle
+    pub fn get_stored_xp(&self, skill: Skill) -> f32 {
+        self.stored_xp[skill]
+    }
+
+    /// Get the levels and xp gained in a skill this cycle
+    pub fn cycle_progress(&self, skill: Skill) -> (u64, f32) {
+        (self.delta_levels[skill], self.delta_xp[skill])
+    }
+
+    /// Puts back progress made this cycle, as returned by `PlayerSkills::cycle_progress`
+    pub fn restore_cycle_progress(&mut self, skill: Skill, levels: u64, xp: f32) {
+        self.delta_levels[skill] = levels;
+        self.delta_xp[skill] = xp;
+    }
+
     pub fn get_unlocked(&self, skill: Skill) -> bool {
         self.unlocked[skill]
     }
@@ -27,6 +27,7 @@ pub enum MenuState {
     #[default]
     None,
     SkillsMenu,
+    WorldMap,
 }
 
 pub struct StatesPlugin;
@@ -0,0 +1,136 @@
+use bevy::{prelude::*, utils::HashMap};
+
+/// Width and height of a `SpatialHash` cell. Nothing looks for neighbours further away than this
+pub const CELL_SIZE: f32 = 64.0;
+/// How far out surrounding enemies swing to come at the player from their own side
+const SURROUND_RADIUS: f32 = 96.0;
+/// Spacing between the sides surrounding enemies pick, so neighbouring entities end up apart
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+/// How an enemy moves around its allies
+#[derive(Debug, Clone, Copy, Reflect, serde::Deserialize)]
+#[serde(default)]
+pub struct Steering {
+    /// How hard the enemy keeps away from allies closer than `separation_radius`
+    pub separation: f32,
+    /// How close allies can get before the enemy moves away from them. At most 64
+    pub separation_radius: f32,
+    /// While chasing, the enemy comes at the player from its own side, so a crowd of them
+    /// surrounds the player instead of piling up behind each other
+    pub surround: bool,
+}
+
+impl Default for Steering {
+    fn default() -> Self {
+        Steering {
+            separation: 1.0,
+            separation_radius: 40.0,
+            surround: false,
+        }
+    }
+}
+
+/// Buckets positions into a grid, so finding everything near a point only looks at the
+/// cells around it
+#[derive(Debug, Default)]
+pub struct SpatialHash {
+    cells: HashMap<IVec2, Vec<(Entity, Vec2)>>,
+}
+
+impl SpatialHash {
+    fn cell(pos: Vec2) -> IVec2 {
+        (pos / CELL_SIZE).floor().as_ivec2()
+    }
+
+    /// Empties the hash, keeping its memory around for the next frame
+    pub fn clear(&mut self) {
+        self.cells.values_mut().for_each(Vec::clear);
+    }
+
+    pub fn insert(&mut self, entity: Entity, pos: Vec2) {
+        self.cells
+            .entry(Self::cell(pos))
+            .or_default()
+            .push((entity, pos));
+    }
+
+    /// Everything in the cells touching the one `pos` is in, which includes everything
+    /// within `CELL_SIZE` of it
+    pub fn nearby(&self, pos: Vec2) -> impl Iterator<Item = &(Entity, Vec2)> {
+        let center = Self::cell(pos);
+        (-1..=1)
+            .flat_map(move |y| (-1..=1).map(move |x| center + IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+    }
+}
+
+/// Adjusts the direction enemies want to move in after `move_enemies` has picked it, pushing
+/// them apart from each other and spreading surrounding enemies around the player
+pub fn steer_enemies(
+    mut enemy_query: Query<(
+        Entity,
+        &crate::enemy::EnemyStats,
+        &crate::enemy::EnemyState,
+        &GlobalTransform,
+        &mut crate::character_controller::CharacterController,
+        &crate::room::NavPath,
+    )>,
+    player_query: Query<&GlobalTransform, With<crate::player::Player>>,
+    mut spatial_hash: Local<SpatialHash>,
+) {
+    let player_pos = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation().truncate());
+
+    spatial_hash.clear();
+    for (entity, _, _, transform, _, _) in enemy_query.iter() {
+        spatial_hash.insert(entity, transform.translation().truncate());
+    }
+
+    for (entity, stats, enemy_state, transform, mut controller, nav_path) in enemy_query.iter_mut()
+    {
+        let steering = stats.steering;
+        let enemy_pos = transform.translation().truncate();
+        let mut desired = controller.desired_direction;
+
+        let chasing = matches!(
+            stats.behavior_state(enemy_state).map(|state| &state.action),
+            Some(crate::behavior::Action::Chase)
+        );
+        // only once there's nothing in the way, so it doesn't swing into obstacles
+        if let Some(player_pos) =
+            player_pos.filter(|_| steering.surround && chasing && nav_path.is_direct())
+        {
+            let distance = player_pos.distance(enemy_pos);
+            if distance > stats.desired_distance + crate::behavior::CLOSE_ENOUGH {
+                // each enemy gets its own side, which it closes in from as it gets nearer
+                let side = Vec2::from_angle(entity.index() as f32 * GOLDEN_ANGLE);
+                let approach = player_pos + side * (distance * 0.5).min(SURROUND_RADIUS);
+                desired = (approach - enemy_pos).normalize_or_zero() * desired.length();
+            }
+        }
+
+        let radius = steering.separation_radius.min(CELL_SIZE);
+        let mut push = Vec2::ZERO;
+        for (other, other_pos) in spatial_hash.nearby(enemy_pos) {
+            let offset = enemy_pos - *other_pos;
+            let distance = offset.length();
+            if *other == entity || distance >= radius {
+                continue;
+            }
+            // enemies stacked right on top of each other still need to go different ways
+            let away = offset
+                .try_normalize()
+                .unwrap_or_else(|| Vec2::from_angle(entity.index() as f32 * GOLDEN_ANGLE));
+            push += away * (1.0 - distance / radius);
+        }
+        desired += push * steering.separation;
+
+        let desired = desired.clamp_length_max(1.0);
+        if controller.desired_direction != desired {
+            controller.desired_direction = desired;
+        }
+    }
+}
@@ -21,8 +21,11 @@ impl Plugin for TextPlugin {
                 update_next_room_text,
                 level_up_text,
                 handle_text_markers,
+                update_text_markers,
                 heal_text,
                 skill_unlocked_text,
+                room_cleared_text,
+                boss_phase_text,
             )
                 .run_if(in_state(crate::states::GameState::InGame)),
         )
@@ -165,35 +168,24 @@ fn level_up_text(
     }
 }
 
-fn spawn_next_room_text(mut commands: Commands, current_room: Res<crate::room::CurrentRoom>) {
-    let directions = [
-        crate::room::CardinalDirection::North,
-        crate::room::CardinalDirection::South,
-        crate::room::CardinalDirection::East,
-        crate::room::CardinalDirection::West,
-    ];
-
-    let room_info = &current_room.info;
-    for dir in directions {
-        let Some(next_room_name) = (match dir {
-            crate::room::CardinalDirection::North => &room_info.north,
-            crate::room::CardinalDirection::South => &room_info.south,
-            crate::room::CardinalDirection::East => &room_info.east,
-            crate::room::CardinalDirection::West => &room_info.west,
-        }) else {
-            // There's no room in that direction, no need for a label
+fn spawn_next_room_text(
+    mut commands: Commands,
+    current_room: Res<crate::room::CurrentRoom>,
+    room_graph: Res<crate::room::RoomGraph>,
+) {
+    for index in 0..current_room.info.exits.len() {
+        let Some(link) = room_graph.link(&current_room.info.name, index) else {
             continue;
         };
-
         commands.spawn((
             SpatialBundle {
                 visibility: Visibility::Hidden,
                 ..Default::default()
             },
-            NextRoomText { side: dir },
+            NextRoomText { exit: index },
             TextMarker {
                 font_size: 18.0,
-                text: format!("{}", next_room_name),
+                text: link.room.clone(),
                 ..Default::default()
             },
             crate::room::RoomObject,
@@ -203,9 +195,17 @@ fn spawn_next_room_text(mut commands: Commands, current_room: Res<crate::room::C
 }
 
 fn update_next_room_text(
-    mut query: Query<(&mut Transform, &TextMarker, &mut Visibility, &NextRoomText)>,
+    mut query: Query<(
+        &mut Transform,
+        &mut TextMarker,
+        &mut Visibility,
+        &NextRoomText,
+    )>,
     camera_query: Query<&GlobalTransform, With<crate::camera::GameCamera>>,
     current_room: Res<crate::room::CurrentRoom>,
+    room_graph: Res<crate::room::RoomGraph>,
+    exit_locks: Res<crate::room::ExitLocks>,
+    room_state: Res<crate::room::PersistentRoomState>,
 ) {
     let Ok(camera_global_transform) = camera_query.get_single() else {
         warn!("update_next_room_text: Couldn't find GameCamera");
@@ -214,33 +214,71 @@ fn update_next_room_text(
     let camera_pos = camera_global_transform.translation().truncate();
     let room_rect = current_room.info.rect;
 
-    for (mut transform, text_marker, mut visibility, next_room_text) in query.iter_mut() {
+    for (mut transform, mut text_marker, mut visibility, next_room_text) in query.iter_mut() {
+        let Some(link) = room_graph.link(&current_room.info.name, next_room_text.exit) else {
+            continue;
+        };
+        let cleared = room_state
+            .rooms
+            .get(&link.room)
+            .is_some_and(|state| state.cleared);
+        let name = if cleared {
+            format!("{} (cleared)", link.room)
+        } else {
+            link.room.clone()
+        };
+        let text = match exit_locks.reason(next_room_text.exit) {
+            Some(reason) => format!("{name}\n({reason})"),
+            None => name,
+        };
+        if text_marker.text != text {
+            text_marker.text = text;
+        }
+
         let Some(aabb2d) = text_marker.text_aabb else {
             continue;
         };
+        let Some(exit) = current_room.info.exits.get(next_room_text.exit) else {
+            continue;
+        };
 
         // the aabb is set at this point, so we can make it visible and position it correctly
         *visibility = Visibility::Inherited;
 
         const PADDING: f32 = 16.0;
 
-        let pos = match next_room_text.side {
-            crate::room::CardinalDirection::North => vec2(
-                camera_pos.x,
-                room_rect.half_size().y - aabb2d.half_size().y - PADDING,
-            ),
-            crate::room::CardinalDirection::South => vec2(
-                camera_pos.x,
-                -room_rect.half_size().y + aabb2d.half_size().y + PADDING,
-            ),
-            crate::room::CardinalDirection::East => vec2(
-                room_rect.half_size().x - aabb2d.half_size().x - PADDING,
-                camera_pos.y,
-            ),
-            crate::room::CardinalDirection::West => vec2(
-                -room_rect.half_size().x + aabb2d.half_size().x + PADDING,
-                camera_pos.y,
-            ),
+        let pos = match exit.location {
+            crate::room::ExitLocation::Wall { .. } => {
+                let (side, from, to) = exit
+                    .wall_range(room_rect)
+                    .expect("wall exits always have a range");
+                // follow the camera along the wall, but stay next to the exit
+                let (along, half_width, inset) = match side {
+                    crate::room::CardinalDirection::North
+                    | crate::room::CardinalDirection::South => (
+                        camera_pos.x,
+                        aabb2d.half_size().x,
+                        aabb2d.half_size().y + PADDING,
+                    ),
+                    crate::room::CardinalDirection::East | crate::room::CardinalDirection::West => {
+                        (
+                            camera_pos.y,
+                            aabb2d.half_size().y,
+                            aabb2d.half_size().x + PADDING,
+                        )
+                    }
+                };
+                let along = if to - from > half_width * 2.0 {
+                    along.clamp(from + half_width, to - half_width)
+                } else {
+                    (from + to) / 2.0
+                };
+                side.inside_point(room_rect, along, inset)
+            }
+            crate::room::ExitLocation::Door { position, size } => {
+                // float above the door
+                position + vec2(0.0, size.y / 2.0 + aabb2d.half_size().y + PADDING)
+            }
         };
 
         transform.translation = pos.extend(transform.translation.z);
@@ -250,23 +288,86 @@ fn update_next_room_text(
 fn room_text(
     mut commands: Commands,
     current_room: Res<crate::room::CurrentRoom>,
+    room_state: Res<crate::room::PersistentRoomState>,
     camera_query: Query<(Entity, &OrthographicProjection), With<crate::camera::GameCamera>>,
     cycle_counter: Res<crate::cycles::CycleCounter>,
-    mut texts_to_spawn: Local<Vec<String>>,
 ) {
     let Ok((camera_entity, ortho_proj)) = camera_query.get_single() else {
         warn!("room_text: can't find game camera");
         return;
     };
 
-    texts_to_spawn.push(current_room.info.name.clone());
-    if current_room.info.name == "Lovely Cottage" {
-        texts_to_spawn.push(format!("Cycle {}", cycle_counter.count + 1));
+    let mut texts = vec![current_room.info.name.clone()];
+    if current_room.info.name == crate::room::START_ROOM {
+        texts.push(format!("Cycle {}", cycle_counter.count + 1));
+    } else {
+        texts.push(format!("Level {}", current_room.level));
+    }
+    if room_state
+        .rooms
+        .get(&current_room.info.name)
+        .is_some_and(|state| state.cleared)
+    {
+        texts.push("Cleared".to_string());
+    }
+
+    spawn_banner(&mut commands, camera_entity, ortho_proj, texts);
+}
+
+fn room_cleared_text(
+    mut commands: Commands,
+    mut events: EventReader<crate::room::RoomClearedEvent>,
+    current_room: Res<crate::room::CurrentRoom>,
+    camera_query: Query<(Entity, &OrthographicProjection), With<crate::camera::GameCamera>>,
+) {
+    let Ok((camera_entity, ortho_proj)) = camera_query.get_single() else {
+        warn!("room_cleared_text: can't find game camera");
+        return;
+    };
+
+    for crate::room::RoomClearedEvent { room } in events.read() {
+        let mut texts = vec![format!("{room} cleared!")];
+        if let Some(reward) = current_room.info.reward.as_ref() {
+            texts.push(reward.describe());
+        }
+        spawn_banner(&mut commands, camera_entity, ortho_proj, texts);
+    }
+}
+
+fn boss_phase_text(
+    mut commands: Commands,
+    mut events: EventReader<crate::boss::BossPhaseChanged>,
+    boss_query: Query<&crate::enemy::BossStats>,
+    camera_query: Query<(Entity, &OrthographicProjection), With<crate::camera::GameCamera>>,
+) {
+    let Ok((camera_entity, ortho_proj)) = camera_query.get_single() else {
+        warn!("boss_phase_text: can't find game camera");
+        return;
+    };
+
+    for crate::boss::BossPhaseChanged { boss, phase } in events.read() {
+        let Ok(boss_stats) = boss_query.get(*boss) else {
+            continue;
+        };
+        let mut texts = vec![boss_stats.name.clone()];
+        if let Some(name) = boss_stats.phases.get(*phase).and_then(|p| p.name.as_ref()) {
+            texts.push(name.clone());
+        }
+        spawn_banner(&mut commands, camera_entity, ortho_proj, texts);
     }
+}
 
-    let n = texts_to_spawn.len();
+/// Spreads lines of text down the middle of the screen for a few seconds.
+/// The first line is written in the fancy font
+fn spawn_banner(
+    commands: &mut Commands,
+    camera_entity: Entity,
+    ortho_proj: &OrthographicProjection,
+    texts: Vec<String>,
+) {
+    let n = texts.len();
     let camera_rect = ortho_proj.area;
-    for (i, text) in texts_to_spawn.drain(..).enumerate() {
+    for (i, text) in texts.into_iter().enumerate() {
         let t = (i + 1) as f32 / (n + 1) as f32;
         let y = camera_rect.max.y * (1.0 - t) + camera_rect.min.y * t;
         let text_entity = commands
@@ -368,6 +469,27 @@ fn handle_text_markers(
     }
 }
 
+/// Keeps spawned text in sync with its `TextMarker::text`
+fn update_text_markers(
+    marker_query: Query<(&TextMarker, &Children), Changed<TextMarker>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (marker, children) in marker_query.iter() {
+        // the text and its drop shadow
+        for child in children.iter() {
+            let Ok(mut text) = text_query.get_mut(*child) else {
+                continue;
+            };
+            let Some(section) = text.sections.first() else {
+                continue;
+            };
+            if section.value != marker.text {
+                text.sections[0].value.clone_from(&marker.text);
+            }
+        }
+    }
+}
+
 fn update_aabb_fields(
     text_query: Query<
         (Entity, &Parent, &bevy::render::primitives::Aabb),
@@ -427,5 +549,6 @@ pub struct FloatingText {
 
 #[derive(Debug, Component)]
 pub struct NextRoomText {
-    pub side: crate::room::CardinalDirection,
+    /// Index of the exit this label belongs to in the current room's `RoomInfo::exits`
+    pub exit: usize,
 }
@@ -0,0 +1,874 @@
+//! Headless validation of the game's content files.
+//!
+//! Run with `cargo run -- --validate [assets directory]`. Every problem found is printed as
+//! `path:line: message`, and the process exits with a non-zero status if there were any.
+
+use std::path::{Path, PathBuf};
+
+use bevy::utils::{HashMap, HashSet};
+use bevy_asset_loader::prelude::{StandardDynamicAsset, StandardDynamicAssetCollection};
+use serde::de::DeserializeOwned;
+
+/// Keys every room's `*.assets.ron` file has to provide
+const REQUIRED_ROOM_KEYS: &[&str] = &[
+    "info",
+    "background_texture",
+    "obstacle_texture",
+    "projectile",
+];
+
+/// Validates everything under the given assets directory, printing any problems found.
+///
+/// Returns the exit code for the process
+pub fn validate_content(assets_dir: impl Into<PathBuf>) -> i32 {
+    let mut validator = Validator {
+        root: assets_dir.into(),
+        ..Default::default()
+    };
+
+    validator.validate_all_files();
+    validator.validate_rooms();
+
+    for problem in validator.problems.iter() {
+        match problem.line {
+            Some(line) => eprintln!("{}:{}: {}", problem.path.display(), line, problem.message),
+            None => eprintln!("{}: {}", problem.path.display(), problem.message),
+        }
+    }
+
+    if validator.problems.is_empty() {
+        println!("validate: no problems found");
+        0
+    } else {
+        eprintln!("validate: found {} problem(s)", validator.problems.len());
+        1
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Problem {
+    path: PathBuf,
+    line: Option<usize>,
+    message: String,
+}
+
+#[derive(Debug, Default)]
+struct Validator {
+    root: PathBuf,
+    /// Contents of every file that has been read so far, keyed by path relative to `root`
+    sources: HashMap<PathBuf, String>,
+    /// Story flags raised by any boss, see `BossStats::story_flag`
+    story_flags: HashSet<String>,
+    problems: Vec<Problem>,
+}
+
+/// A room, as far as the validator is concerned
+struct ValidatedRoom {
+    info_path: PathBuf,
+    info: crate::room::RoomInfo,
+}
+
+impl Validator {
+    fn report(&mut self, path: &Path, line: Option<usize>, message: impl Into<String>) {
+        let problem = Problem {
+            path: self.root.join(path),
+            line,
+            message: message.into(),
+        };
+        // files can be checked more than once, only report each problem the first time
+        if !self.problems.contains(&problem) {
+            self.problems.push(problem);
+        }
+    }
+
+    /// Reads a file relative to the assets directory, reporting a problem if it can't be read
+    fn read(&mut self, path: &Path) -> Option<String> {
+        if let Some(source) = self.sources.get(path) {
+            return Some(source.clone());
+        }
+        match std::fs::read_to_string(self.root.join(path)) {
+            Ok(source) => {
+                self.sources.insert(path.to_path_buf(), source.clone());
+                Some(source)
+            }
+            Err(e) => {
+                self.report(path, None, format!("couldn't read file: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Reads and parses a RON file, reporting a problem if either step fails
+    fn parse<T: DeserializeOwned>(&mut self, path: &Path) -> Option<T> {
+        let source = self.read(path)?;
+        match ron::from_str::<T>(&source) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.report(
+                    path,
+                    Some(e.position.line),
+                    format!("couldn't parse: {}", e.code),
+                );
+                None
+            }
+        }
+    }
+
+    /// The line of the first occurrence of `needle` in the given file
+    fn line_of(&self, path: &Path, needle: &str) -> Option<usize> {
+        let source = self.sources.get(path)?;
+        source
+            .lines()
+            .position(|line| line.contains(needle))
+            .map(|index| index + 1)
+    }
+
+    /// Parses every content file under the assets directory, whether or not it's referenced
+    /// from anywhere
+    fn validate_all_files(&mut self) {
+        let mut files = Vec::new();
+        collect_files(&self.root, Path::new(""), &mut files);
+        files.sort();
+
+        for path in files {
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if file_name.ends_with(".assets.ron") {
+                self.validate_asset_collection(&path);
+            } else if file_name.ends_with(".info.ron") {
+                self.parse::<crate::room::RoomInfo>(&path);
+            } else if file_name.ends_with(".enemy.ron") {
+                self.validate_enemy_stats(&path);
+            } else if file_name.ends_with(".boss.ron") {
+                self.validate_boss_stats(&path);
+            } else if file_name.ends_with(".difficulty.ron") {
+                self.validate_difficulty_curve(&path);
+            }
+        }
+    }
+
+    /// Checks that every file referenced by a dynamic asset collection exists
+    fn validate_asset_collection(
+        &mut self,
+        path: &Path,
+    ) -> Option<HashMap<String, StandardDynamicAsset>> {
+        let StandardDynamicAssetCollection(collection) = self.parse(path)?;
+
+        let mut keys: Vec<&String> = collection.keys().collect();
+        keys.sort();
+        for key in keys {
+            for asset_path in asset_paths(&collection[key]) {
+                if !self.root.join(asset_path).is_file() {
+                    let line = self.line_of(path, asset_path);
+                    self.report(
+                        path,
+                        line,
+                        format!("`{key}` refers to missing file `{asset_path}`"),
+                    );
+                }
+            }
+        }
+
+        Some(collection)
+    }
+
+    fn validate_enemy_stats(&mut self, path: &Path) {
+        let Some(stats) = self.parse::<crate::enemy::EnemyStats>(path) else {
+            return;
+        };
+        self.check_enemy_stats(path, &stats);
+    }
+
+    fn validate_boss_stats(&mut self, path: &Path) {
+        let Some(boss_stats) = self.parse::<crate::enemy::BossStats>(path) else {
+            return;
+        };
+        if boss_stats.scale <= 0.0 {
+            let line = self.line_of(path, "scale:");
+            self.report(path, line, "boss scale must be greater than 0");
+        }
+        self.check_enemy_stats(path, &boss_stats.stats);
+        self.check_boss_phases(path, &boss_stats.phases);
+        if let Some(flag) = boss_stats.story_flag {
+            self.story_flags.insert(flag);
+        }
+    }
+
+    /// Checks that a boss's phases come in order and make sense on their own
+    fn check_boss_phases(&mut self, path: &Path, phases: &[crate::boss::BossPhase]) {
+        let mut previous = 1.0;
+        for phase in phases.iter() {
+            let line = self.line_of(path, &format!("health_below: {}", phase.health_below));
+            if phase.health_below <= 0.0 || phase.health_below > 1.0 {
+                self.report(path, line, "phase health_below must be between 0 and 1");
+            } else if phase.health_below >= previous {
+                self.report(
+                    path,
+                    line,
+                    "phases must be listed from the highest health_below down",
+                );
+            }
+            previous = phase.health_below;
+            if let Some(enemy_type) = &phase.enemy_type {
+                self.check_enemy_type(path, enemy_type);
+            }
+            if phase.speed.is_some_and(|speed| speed < 0.0) {
+                self.report(path, line, "phase speed can't be negative");
+            }
+            if phase.invulnerable < 0.0 {
+                self.report(path, line, "phase invulnerable time can't be negative");
+            }
+            if let Some(behavior) = &phase.behavior {
+                self.check_behavior(path, behavior);
+            }
+            for summon in phase.summons.iter() {
+                let line = self.line_of(path, &summon.enemy);
+                if summon.count == 0 {
+                    self.report(path, line, "summon count must be greater than 0");
+                }
+                if !summon.enemy.ends_with(".enemy.ron") {
+                    self.report(
+                        path,
+                        line,
+                        format!(
+                            "summons should refer to a `*.enemy.ron` file, found `{}`",
+                            summon.enemy
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks that everything a room's boss summons is in the room's roster
+    fn check_summons(&mut self, boss_path: &Path, info: &crate::room::RoomInfo) {
+        let Some(boss_stats) = self.parse::<crate::enemy::BossStats>(boss_path) else {
+            return;
+        };
+        for summon in boss_stats
+            .phases
+            .iter()
+            .flat_map(|phase| phase.summons.iter())
+        {
+            if !info.roster.iter().any(|entry| entry.stats == summon.enemy) {
+                let line = self.line_of(boss_path, &summon.enemy);
+                self.report(
+                    boss_path,
+                    line,
+                    format!(
+                        "boss summons `{}`, but it isn't in the roster of {}",
+                        summon.enemy, info.name
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_enemy_stats(&mut self, path: &Path, stats: &crate::enemy::EnemyStats) {
+        if stats.health <= 0.0 {
+            let line = self.line_of(path, "health:");
+            self.report(path, line, "health must be greater than 0");
+        }
+        self.check_enemy_type(path, &stats.enemy_type);
+        if stats.chase_radius < stats.alert_radius {
+            let line = self.line_of(path, "chase_radius:");
+            self.report(
+                path,
+                line,
+                format!(
+                    "chase_radius ({}) must be at least alert_radius ({})",
+                    stats.chase_radius, stats.alert_radius
+                ),
+            );
+        }
+        if let Some(cone) = stats.view_cone {
+            let line = self.line_of(path, "view_cone:");
+            if cone.angle <= 0.0 || cone.angle > 360.0 {
+                self.report(path, line, "view cone angle must be between 0 and 360");
+            }
+            if cone.facing == bevy::math::Vec2::ZERO {
+                self.report(path, line, "view cone facing can't be zero");
+            }
+        }
+        if stats.shout_radius < 0.0 {
+            let line = self.line_of(path, "shout_radius:");
+            self.report(path, line, "shout_radius can't be negative");
+        }
+        if stats.hearing_radius.is_some_and(|radius| radius <= 0.0) {
+            let line = self.line_of(path, "hearing_radius:");
+            self.report(path, line, "hearing_radius must be greater than 0");
+        }
+        if stats.steering.separation < 0.0 {
+            let line = self.line_of(path, "separation:");
+            self.report(path, line, "steering separation can't be negative");
+        }
+        if stats.steering.separation_radius <= 0.0 {
+            let line = self.line_of(path, "separation_radius:");
+            self.report(
+                path,
+                line,
+                "steering separation_radius must be greater than 0",
+            );
+        } else if stats.steering.separation_radius > crate::steering::CELL_SIZE {
+            let line = self.line_of(path, "separation_radius:");
+            self.report(
+                path,
+                line,
+                format!(
+                    "steering separation_radius can be at most {}",
+                    crate::steering::CELL_SIZE
+                ),
+            );
+        }
+        if stats.search_time < 0.0 {
+            let line = self.line_of(path, "search_time:");
+            self.report(path, line, "search_time can't be negative");
+        }
+        if let Some(behavior) = &stats.behavior {
+            self.check_behavior(path, behavior);
+        }
+    }
+
+    fn check_enemy_type(&mut self, path: &Path, enemy_type: &crate::enemy::EnemyType) {
+        let crate::enemy::EnemyType::Ranged {
+            projectile_speed,
+            pattern,
+            ..
+        } = enemy_type
+        else {
+            return;
+        };
+        let line = self.line_of(path, "pattern:");
+        if pattern.count == 0 {
+            self.report(path, line, "pattern count must be greater than 0");
+        }
+        if pattern.burst == 0 {
+            self.report(path, line, "pattern burst must be greater than 0");
+        }
+        if pattern.burst_delay < 0.0 {
+            self.report(path, line, "pattern burst_delay can't be negative");
+        }
+        if !(0.0..=360.0).contains(&pattern.spread) {
+            self.report(path, line, "pattern spread must be between 0 and 360");
+        }
+        if !(0.0..=1.0).contains(&pattern.lead) {
+            self.report(path, line, "pattern lead must be between 0 and 1");
+        }
+        if pattern.lead > 0.0 && *projectile_speed <= 0.0 {
+            let line = self.line_of(path, "projectile_speed:");
+            self.report(
+                path,
+                line,
+                "projectile_speed must be greater than 0 to lead shots",
+            );
+        }
+    }
+
+    /// Checks that a behavior's states and transitions fit together
+    fn check_behavior(&mut self, path: &Path, behavior: &crate::behavior::Behavior) {
+        use crate::behavior::{Action, Condition};
+        if behavior.states.is_empty() {
+            let line = self.line_of(path, "behavior:");
+            self.report(path, line, "behavior needs at least one state");
+        }
+        for (index, state) in behavior.states.iter().enumerate() {
+            let line = self.line_of(path, &format!("name: \"{}\"", state.name));
+            if behavior.find(&state.name) != Some(index) {
+                self.report(
+                    path,
+                    line,
+                    format!("more than one state is called `{}`", state.name),
+                );
+            }
+            match &state.action {
+                Action::Patrol { points } if points.is_empty() => {
+                    self.report(path, line, "patrol needs at least one point");
+                }
+                Action::Kite { distance } if *distance <= 0.0 => {
+                    self.report(path, line, "kite distance must be greater than 0");
+                }
+                _ => {}
+            }
+            for transition in state.transitions.iter() {
+                if behavior.find(&transition.to).is_none() {
+                    self.report(
+                        path,
+                        line,
+                        format!(
+                            "transition goes to `{}`, but there's no such state",
+                            transition.to
+                        ),
+                    );
+                }
+                for condition in transition.when.iter() {
+                    match *condition {
+                        Condition::HealthBelow(fraction) | Condition::HealthAbove(fraction)
+                            if !(0.0..=1.0).contains(&fraction) =>
+                        {
+                            self.report(path, line, "health fraction must be between 0 and 1");
+                        }
+                        Condition::After(seconds) if seconds < 0.0 => {
+                            self.report(path, line, "transition time can't be negative");
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_difficulty_curve(&mut self, path: &Path) {
+        let Some(curve) = self.parse::<crate::difficulty::DifficultyCurve>(path) else {
+            return;
+        };
+        if curve.points.is_empty() {
+            let line = self.line_of(path, "points:");
+            self.report(path, line, "difficulty curve needs at least one point");
+        }
+        for pair in curve.points.windows(2) {
+            if pair[1].level <= pair[0].level {
+                let line = self.line_of(path, &format!("level: {}", pair[1].level));
+                self.report(path, line, "difficulty points must be sorted by level");
+            }
+        }
+        for point in curve.points.iter() {
+            let multipliers = [
+                point.health,
+                point.damage,
+                point.speed,
+                point.projectile_speed,
+            ];
+            if multipliers.iter().any(|m| *m <= 0.0) {
+                let line = self.line_of(path, &format!("level: {}", point.level));
+                self.report(path, line, "difficulty multipliers must be greater than 0");
+            }
+        }
+    }
+
+    /// Checks the room list, each room's assets, and the links between rooms
+    fn validate_rooms(&mut self) {
+        let manifest_path = PathBuf::from(crate::assets::ROOM_MANIFEST_PATH);
+        let Some(manifest) = self.parse::<crate::assets::RoomManifest>(&manifest_path) else {
+            return;
+        };
+
+        let mut rooms: Vec<ValidatedRoom> = Vec::new();
+        for room_file in manifest.rooms.iter() {
+            let room_path = PathBuf::from(room_file);
+            if !self.root.join(&room_path).is_file() {
+                let line = self.line_of(&manifest_path, room_file);
+                self.report(
+                    &manifest_path,
+                    line,
+                    format!("room file `{room_file}` doesn't exist"),
+                );
+                continue;
+            }
+            if let Some(room) = self.validate_room_assets(&room_path) {
+                rooms.push(room);
+            }
+        }
+
+        // room names have to be unique
+        let mut names: HashMap<String, PathBuf> = HashMap::default();
+        for room in rooms.iter() {
+            if let Some(first) = names.get(&room.info.name) {
+                let line = self.line_of(&room.info_path, "name:");
+                let message = format!(
+                    "room name `{}` is already used by {}",
+                    room.info.name,
+                    self.root.join(first).display()
+                );
+                self.report(&room.info_path, line, message);
+            } else {
+                names.insert(room.info.name.clone(), room.info_path.clone());
+            }
+        }
+
+        if !rooms.iter().any(|r| r.info.name == crate::room::START_ROOM) {
+            self.report(
+                &manifest_path,
+                None,
+                format!("no room is named `{}`", crate::room::START_ROOM),
+            );
+        }
+
+        // a room without exits can't be reached, and generated room graphs give up on it
+        for room in rooms.iter() {
+            if room.info.name != crate::room::START_ROOM && room.info.exits.is_empty() {
+                let line = self.line_of(&room.info_path, "name:");
+                self.report(
+                    &room.info_path,
+                    line,
+                    "room has no exits, so it can't be reached",
+                );
+            }
+        }
+
+        // every boss has to be reachable from the start room
+        let mut reachable = HashSet::from([crate::room::START_ROOM]);
+        let mut queue = vec![crate::room::START_ROOM];
+        while let Some(name) = queue.pop() {
+            let Some(room) = rooms.iter().find(|r| r.info.name == name) else {
+                continue;
+            };
+            for exit in room.info.exits.iter() {
+                if reachable.insert(exit.target.as_str()) {
+                    queue.push(exit.target.as_str());
+                }
+            }
+        }
+        for room in rooms.iter() {
+            if room.info.boss && !reachable.contains(room.info.name.as_str()) {
+                let line = self.line_of(&room.info_path, "boss:");
+                self.report(
+                    &room.info_path,
+                    line,
+                    format!(
+                        "boss room can't be reached from `{}`",
+                        crate::room::START_ROOM
+                    ),
+                );
+            }
+        }
+
+        // every exit has to lead to a room that exists, and that room has to lead back
+        for room in rooms.iter() {
+            for exit in room.info.exits.iter() {
+                let line = self.line_of(&room.info_path, &format!("\"{}\"", exit.target));
+                let Some(target) = rooms.iter().find(|r| r.info.name == exit.target) else {
+                    self.report(
+                        &room.info_path,
+                        line,
+                        format!("exit leads to unknown room `{}`", exit.target),
+                    );
+                    continue;
+                };
+                if !target.info.exits.iter().any(|e| e.target == room.info.name) {
+                    self.report(
+                        &room.info_path,
+                        line,
+                        format!(
+                            "exit leads to `{}`, but `{}` has no exit leading back to `{}`",
+                            exit.target, exit.target, room.info.name
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn validate_room_assets(&mut self, path: &Path) -> Option<ValidatedRoom> {
+        let collection = self.validate_asset_collection(path)?;
+
+        for key in REQUIRED_ROOM_KEYS {
+            if !collection.contains_key(*key) {
+                self.report(path, None, format!("missing required key `{key}`"));
+            }
+        }
+
+        let boss_path = collection.get("boss_stats").and_then(file_path);
+        if let Some(boss_path) = boss_path {
+            self.expect_extension(path, "boss_stats", boss_path, ".boss.ron");
+        }
+
+        let info_path = PathBuf::from(collection.get("info").and_then(file_path)?);
+        self.expect_extension(path, "info", info_path.to_str()?, ".info.ron");
+        let info = self.parse::<crate::room::RoomInfo>(&info_path)?;
+
+        if info.boss && boss_path.is_none() {
+            let line = self.line_of(&info_path, "boss:");
+            self.report(
+                &info_path,
+                line,
+                format!(
+                    "room has a boss, but {} doesn't provide `boss_stats`",
+                    self.root.join(path).display()
+                ),
+            );
+        }
+
+        self.check_roster(&info_path, &info);
+        if let Some(boss_path) = boss_path {
+            self.check_summons(Path::new(boss_path), &info);
+        }
+        self.check_hazards(&info_path, &info);
+        self.check_layout(&info_path, &info);
+        self.check_requirements(&info_path, &info);
+        self.check_reward(&info_path, &info);
+        self.check_respawn(&info_path, &info);
+        self.check_obstacle_kinds(&info_path, &info);
+
+        Some(ValidatedRoom { info_path, info })
+    }
+
+    /// Checks that every exit requirement can actually be met
+    fn check_requirements(&mut self, path: &Path, info: &crate::room::RoomInfo) {
+        for exit in info.exits.iter() {
+            let line = self.line_of(path, &format!("\"{}\"", exit.target));
+            for requirement in exit.requires.iter() {
+                match requirement {
+                    crate::room::ExitRequirement::BossDefeated if !info.boss => {
+                        self.report(
+                            path,
+                            line,
+                            "exit requires the boss to be defeated, but the room has no boss",
+                        );
+                    }
+                    crate::room::ExitRequirement::StoryFlag { flag, .. }
+                        if !self.story_flags.contains(flag) =>
+                    {
+                        self.report(
+                            path,
+                            line,
+                            format!("exit requires story flag `{flag}`, but no boss raises it"),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Checks that the room's reward can actually be earned and collected
+    fn check_reward(&mut self, path: &Path, info: &crate::room::RoomInfo) {
+        let Some(reward) = &info.reward else {
+            return;
+        };
+        let line = self.line_of(path, "reward:");
+        if info.num_enemies == 0 && !info.boss {
+            self.report(
+                path,
+                line,
+                "room has a reward, but no enemies to defeat for it",
+            );
+        }
+        match reward {
+            crate::room::RoomReward::Xp { skills, xp } => {
+                if skills.is_empty() {
+                    self.report(path, line, "xp reward doesn't name any skills");
+                }
+                if *xp <= 0.0 {
+                    self.report(path, line, "reward xp must be greater than 0");
+                }
+            }
+            crate::room::RoomReward::FullHeal => {}
+            crate::room::RoomReward::Chest { position, xp } => {
+                if position.is_some_and(|position| !info.rect.contains(position)) {
+                    self.report(path, line, "chest is outside the room");
+                }
+                if *xp <= 0.0 {
+                    self.report(path, line, "reward xp must be greater than 0");
+                }
+            }
+        }
+    }
+
+    fn check_respawn(&mut self, path: &Path, info: &crate::room::RoomInfo) {
+        let line = self.line_of(path, "respawn:");
+        match info.respawn {
+            crate::room::RespawnPolicy::AfterAway { seconds } if seconds < 0.0 => {
+                self.report(path, line, "respawn seconds can't be negative");
+            }
+            crate::room::RespawnPolicy::Trickle { interval } if interval <= 0.0 => {
+                self.report(path, line, "respawn interval must be greater than 0");
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks that obstacle kinds can actually be picked, broken and pushed
+    fn check_obstacle_kinds(&mut self, path: &Path, info: &crate::room::RoomInfo) {
+        for entry in info.obstacle_kinds.iter() {
+            let line = self.line_of(path, "obstacle_kinds:");
+            if entry.weight < 0.0 {
+                self.report(path, line, "obstacle weight can't be negative");
+            }
+            match entry.kind {
+                crate::room::ObstacleKind::Destructible { health } if health <= 0.0 => {
+                    self.report(path, line, "obstacle health must be greater than 0");
+                }
+                crate::room::ObstacleKind::Pushable { mass } if mass <= 0.0 => {
+                    self.report(path, line, "obstacle mass must be greater than 0");
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Checks that every roster entry refers to files that exist, and that its counts make sense
+    fn check_roster(&mut self, path: &Path, info: &crate::room::RoomInfo) {
+        for entry in info.roster.iter() {
+            let line = self.line_of(path, &entry.stats);
+            if !entry.stats.ends_with(".enemy.ron") {
+                self.report(
+                    path,
+                    line,
+                    format!(
+                        "roster stats should refer to a `*.enemy.ron` file, found `{}`",
+                        entry.stats
+                    ),
+                );
+            }
+            let files = [
+                Some(&entry.stats),
+                Some(&entry.texture),
+                entry.projectile.as_ref(),
+            ];
+            for file in files.into_iter().flatten() {
+                if !self.root.join(file).is_file() {
+                    let line = self.line_of(path, file);
+                    self.report(
+                        path,
+                        line,
+                        format!("roster refers to missing file `{file}`"),
+                    );
+                }
+            }
+            if entry.weight < 0.0 {
+                self.report(path, line, "roster weight can't be negative");
+            }
+            if entry.max.is_some_and(|max| max < entry.min) {
+                self.report(path, line, "roster max can't be less than min");
+            }
+        }
+
+        let total_min: usize = info.roster.iter().map(|entry| entry.min).sum();
+        if total_min > info.num_enemies {
+            let line = self.line_of(path, "num_enemies:");
+            self.report(
+                path,
+                line,
+                format!(
+                    "roster minimums add up to {total_min}, more than num_enemies ({})",
+                    info.num_enemies
+                ),
+            );
+        }
+        if info.num_enemies > 0 && info.roster.is_empty() {
+            let line = self.line_of(path, "num_enemies:");
+            self.report(
+                path,
+                line,
+                "room has enemies, but no roster to pick them from",
+            );
+        }
+    }
+
+    fn check_hazards(&mut self, path: &Path, info: &crate::room::RoomInfo) {
+        let line = self.line_of(path, "hazards:");
+        for hazard in info.hazards.iter() {
+            if !info.rect.contains(hazard.rect.min) || !info.rect.contains(hazard.rect.max) {
+                self.report(
+                    path,
+                    line,
+                    format!(
+                        "hazard from {} to {} doesn't fit in the room",
+                        hazard.rect.min, hazard.rect.max
+                    ),
+                );
+            }
+            match hazard.kind {
+                crate::room::HazardKind::Lava { interval, .. }
+                | crate::room::HazardKind::Consecrated { interval, .. }
+                    if interval <= 0.0 =>
+                {
+                    self.report(path, line, "hazard interval must be greater than 0");
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Checks that everything placed by hand actually fits in the room
+    fn check_layout(&mut self, path: &Path, info: &crate::room::RoomInfo) {
+        let Some(layout) = &info.layout else {
+            return;
+        };
+        let line = self.line_of(path, "layout:");
+
+        let positions = layout
+            .obstacles
+            .iter()
+            .chain(layout.spawners.iter().map(|s| &s.position));
+        for position in positions {
+            if !info.rect.contains(*position) {
+                self.report(
+                    path,
+                    line,
+                    format!("layout position {position} is outside the room"),
+                );
+            }
+        }
+
+        for spawner in layout.spawners.iter() {
+            if let crate::room::SpawnerType::Roster(index) = spawner.ty {
+                if index >= info.roster.len() {
+                    self.report(
+                        path,
+                        line,
+                        format!("layout spawner uses roster entry {index}, which doesn't exist"),
+                    );
+                }
+            }
+        }
+
+        let has_boss_spawner = layout
+            .spawners
+            .iter()
+            .any(|s| matches!(s.ty, crate::room::SpawnerType::Boss));
+        if has_boss_spawner && !info.boss {
+            self.report(
+                path,
+                line,
+                "layout places a boss spawner, but the room doesn't have a boss",
+            );
+        }
+    }
+
+    fn expect_extension(&mut self, path: &Path, key: &str, asset_path: &str, extension: &str) {
+        if !asset_path.ends_with(extension) {
+            let line = self.line_of(path, asset_path);
+            self.report(
+                path,
+                line,
+                format!("`{key}` should refer to a `*{extension}` file, found `{asset_path}`"),
+            );
+        }
+    }
+}
+
+/// The file paths referenced by a dynamic asset
+fn asset_paths(asset: &StandardDynamicAsset) -> Vec<&str> {
+    match asset {
+        StandardDynamicAsset::File { path } | StandardDynamicAsset::Image { path, .. } => {
+            vec![path.as_str()]
+        }
+        StandardDynamicAsset::Files { paths } => paths.iter().map(String::as_str).collect(),
+        _ => vec![],
+    }
+}
+
+/// The path of a dynamic asset loaded from a single file
+fn file_path(asset: &StandardDynamicAsset) -> Option<&str> {
+    match asset {
+        StandardDynamicAsset::File { path } => Some(path.as_str()),
+        _ => None,
+    }
+}
+
+/// Recursively lists every file under `root/dir`, relative to `root`
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(root.join(dir)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = dir.join(entry.file_name());
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => collect_files(root, &path, files),
+            Ok(_) => files.push(path),
+            Err(_) => {}
+        }
+    }
+}